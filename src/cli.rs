@@ -1,18 +1,26 @@
-use crate::config::Config;
+use crate::config::{self, Config};
+use crate::diff;
+use crate::ignore::IgnoreList;
 use crate::interactive;
-use crate::models::{BundleMode, NoteFormat};
+use crate::logging::{self, Verbosity};
+use crate::models::{BundleMode, CsvLayout, NoteFormat, ParseWarning, WarningSeverity};
 use crate::parser::{self, ParseOptions};
 use crate::render::{self, OutputFormat};
-use crate::reports::BatchReport;
+use crate::reports::{self, BatchReport};
 use crate::samples;
 use crate::selftest;
+use crate::stats;
 use crate::util;
 use crate::validate::{self, Severity, Template, ValidationIssue};
+use crate::verify;
+use crate::wordfreq;
 use anyhow::{anyhow, Result};
 use clap::{Args, Parser, Subcommand};
 use glob::glob;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::mpsc::RecvTimeoutError;
 use std::time::Instant;
 
 #[derive(Parser)]
@@ -23,6 +31,12 @@ use std::time::Instant;
     after_help = "Examples:\n  clinote validate notes.txt --template soap --strict\n  clinote preview notes.txt --template hp\n  clinote init --path clinote.toml\n  clinote demo\n"
 )]
 pub struct Cli {
+    /// Suppress informational messages; errors are still printed.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Print per-file progress to stderr.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -37,6 +51,14 @@ pub enum Commands {
     Init(InitArgs),
     Demo(DemoArgs),
     Selftest(SelftestArgs),
+    Templates(TemplatesArgs),
+    Verify(VerifyArgs),
+    Wordfreq(WordfreqArgs),
+    Report(ReportArgs),
+    ResolveHeading(ResolveHeadingArgs),
+    Stats(StatsArgs),
+    Diff(DiffArgs),
+    Merge(MergeArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -47,14 +69,32 @@ pub struct ParseArgs {
     pub format: NoteFormat,
     #[arg(long)]
     pub out: PathBuf,
-    #[arg(long, value_enum)]
-    pub out_format: OutputFormat,
+    #[arg(long, value_enum, value_delimiter = ',', required = true)]
+    pub out_format: Vec<OutputFormat>,
     #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
     #[arg(long, value_enum)]
     pub bundle: Option<BundleMode>,
     #[arg(long)]
     pub interactive: bool,
+    #[arg(long)]
+    pub explain: bool,
+    /// Drop sections whose confidence is below this value.
+    #[arg(long, default_value_t = 0.0)]
+    pub min_confidence: f32,
+    /// Run the pipeline but only report what would be written.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Decode input with this encoding (e.g. `windows-1252`) instead of
+    /// requiring UTF-8.
+    #[arg(long)]
+    pub encoding: Option<String>,
+    /// Reorder sections to the format's `section_order`, with empty
+    /// placeholders for missing required sections.
+    #[arg(long)]
+    pub sort_sections: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -67,12 +107,63 @@ pub struct BatchArgs {
     pub format: NoteFormat,
     #[arg(long)]
     pub out_dir: PathBuf,
-    #[arg(long, value_enum)]
-    pub out_format: OutputFormat,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        required_unless_present = "summary_only"
+    )]
+    pub out_format: Vec<OutputFormat>,
     #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
     #[arg(long, value_enum)]
     pub bundle: Option<BundleMode>,
+    #[arg(long, default_value_t = 0)]
+    pub skip: usize,
+    #[arg(long)]
+    pub limit: Option<usize>,
+    #[arg(long)]
+    pub collect_unmapped: Option<PathBuf>,
+    /// Fail files in which any note collapsed to the fallback section; nothing
+    /// is written for them.
+    #[arg(long)]
+    pub strict_structure: bool,
+    #[arg(long)]
+    pub max_warnings: Option<usize>,
+    #[arg(long, default_value = util::DEFAULT_OUT_TEMPLATE)]
+    pub out_template: String,
+    #[arg(long)]
+    pub timeout_ms: Option<u64>,
+    /// Parse and count only; skip rendering and write just the batch reports.
+    #[arg(long)]
+    pub summary_only: bool,
+    /// Also validate every note and write `validation_report.json`.
+    #[arg(long)]
+    pub validate: bool,
+    /// Template for --validate; defaults to the one matching --format.
+    #[arg(long, value_enum, requires = "validate")]
+    pub template: Option<Template>,
+    /// Drop sections whose confidence is below this value.
+    #[arg(long, default_value_t = 0.0)]
+    pub min_confidence: f32,
+    /// Also write every note, in file order, as one JSON array to `notes.json`.
+    #[arg(long)]
+    pub combined: bool,
+    /// Run the pipeline but only report what would be written; the batch
+    /// report is printed to stdout and no files or directories are created.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Decode input with this encoding (e.g. `windows-1252`) instead of
+    /// requiring UTF-8.
+    #[arg(long)]
+    pub encoding: Option<String>,
+    /// Reorder written sections to the format's `section_order`, with empty
+    /// placeholders for missing required sections; reports still count the
+    /// sections as detected.
+    #[arg(long)]
+    pub sort_sections: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -88,19 +179,31 @@ pub struct SampleArgs {
 #[derive(Args, Debug, Clone)]
 #[command(
     about = "Validate a clinical note or config",
-    long_about = "Validate an input note against a template or validate a config file.\nExamples:\n  clinote validate notes.txt --template soap --strict\n  clinote validate --config clinote.toml\n"
+    long_about = "Validate an input note against a template or validate a config file.\nExamples:\n  clinote validate notes.txt --template soap --strict\n  clinote validate notes.txt --template-name \"Nursing Assessment\" --config clinote.toml\n  clinote validate --config clinote.toml\nExit codes:\n  0  no errors (and no warnings with --fail-on-warning)\n  1  runtime failure, such as an unreadable input or config\n  2  validation errors\n  3  warnings with --fail-on-warning, or more than --max-warnings warnings\n"
 )]
 pub struct ValidateArgs {
     #[arg(value_name = "INPUT")]
-    pub input: Option<PathBuf>,
+    pub input: Vec<PathBuf>,
     #[arg(long, value_enum)]
     pub template: Option<Template>,
+    /// A `[[templates]]` entry from the config, instead of a built-in template.
+    #[arg(long, conflicts_with = "template")]
+    pub template_name: Option<String>,
     #[arg(long)]
     pub strict: bool,
     #[arg(long)]
     pub json: bool,
     #[arg(long)]
+    pub no_unknown_sections: bool,
+    #[arg(long)]
+    pub max_warnings: Option<usize>,
+    /// Exit with code 3 when any warning is reported (errors still exit 2).
+    #[arg(long)]
+    pub fail_on_warning: bool,
+    #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -114,7 +217,11 @@ pub struct PreviewArgs {
     #[arg(long, value_enum)]
     pub template: Option<Template>,
     #[arg(long)]
+    pub json: bool,
+    #[arg(long)]
     pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -125,6 +232,14 @@ pub struct PreviewArgs {
 pub struct InitArgs {
     #[arg(long, default_value = "clinote.toml")]
     pub path: PathBuf,
+    #[arg(long)]
+    pub full: bool,
+    /// Print the config to stdout instead of writing it.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// With --dry-run, print the defaults as JSON instead of TOML.
+    #[arg(long, requires = "dry_run", conflicts_with = "full")]
+    pub json: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -153,10 +268,155 @@ pub struct SelftestArgs {
     pub json: bool,
     #[arg(long)]
     pub out: Option<PathBuf>,
+    #[arg(long, value_delimiter = ',', default_value = "txt")]
+    pub ext: Vec<String>,
+    #[arg(long)]
+    pub max_warnings: Option<usize>,
+    /// With --out, write outputs and issues only for failing files.
+    #[arg(long, requires = "out")]
+    pub failures_only: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "List available validation templates",
+    long_about = "List validation templates with their required and optional sections.\nExample:\n  clinote templates --json\n"
+)]
+pub struct TemplatesArgs {
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Check that parsing keeps every source line",
+    long_about = "Parse a note and report source lines that do not land in any section.\nExample:\n  clinote verify --input note.txt --template soap\n"
+)]
+pub struct VerifyArgs {
+    #[arg(long)]
+    pub input: PathBuf,
+    #[arg(long, value_enum)]
+    pub template: Option<Template>,
+    #[arg(long)]
+    pub json: bool,
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Write sections, parser warnings, and validation issues per note",
+    long_about = "Parse and validate a note file and write one JSON document with, per note, the sections, parser warnings, and validation issues.\nExample:\n  clinote report --input note.txt --template soap --out report.json\n"
+)]
+pub struct ReportArgs {
+    #[arg(long)]
+    pub input: PathBuf,
+    #[arg(long, value_enum)]
+    pub template: Option<Template>,
+    #[arg(long)]
+    pub out: PathBuf,
+    #[arg(long)]
+    pub strict: bool,
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Show which canonical section a heading maps to",
+    long_about = "Resolve heading text against the config's aliases, the built-in heading map, and configured section names, without parsing a note.\n--format also checks that format's [formats.<format>.heading_aliases] first, as the parser does.\nExamples:\n  clinote resolve-heading \"Hx\" --config clinote.toml\n  clinote resolve-heading \"Impression:\" --json\n  clinote resolve-heading \"Hospital Course\" --format discharge\n"
+)]
+pub struct ResolveHeadingArgs {
+    #[arg(value_name = "HEADING")]
+    pub heading: String,
+    #[arg(long, value_enum)]
+    pub format: Option<NoteFormat>,
+    #[arg(long)]
+    pub json: bool,
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Count frequent words in a section across structured output",
+    long_about = "Load structured JSON output and list the most frequent tokens in one section.\nExample:\n  clinote wordfreq --input-dir outputs --section Assessment --top 20\n"
+)]
+pub struct WordfreqArgs {
+    #[arg(long)]
+    pub input_dir: PathBuf,
+    #[arg(long)]
+    pub section: String,
+    #[arg(long, default_value_t = 20)]
+    pub top: usize,
+    #[arg(long, default_value_t = 1)]
+    pub min_count: usize,
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Summarize section frequencies across a directory of notes",
+    long_about = "Parse every matching file and report, per section, how many notes contain it, its presence rate, and its average length, without rendering output.\nExample:\n  clinote stats --input-dir notes --format soap --json\n"
+)]
+pub struct StatsArgs {
+    #[arg(long)]
+    pub input_dir: PathBuf,
+    #[arg(long)]
+    pub glob: Option<String>,
+    #[arg(long, value_enum)]
+    pub format: NoteFormat,
+    #[arg(long)]
+    pub json: bool,
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Compare two structured JSON outputs",
+    long_about = "Compare two JSON note files, or two directories of them paired by file name, and report format changes and added, removed, or changed sections per note. Notes are aligned by note_index. Nothing is written.\nExamples:\n  clinote diff before/note.json after/note.json\n  clinote diff before_dir after_dir --json\n"
+)]
+pub struct DiffArgs {
+    #[arg(value_name = "BEFORE")]
+    pub before: PathBuf,
+    #[arg(value_name = "AFTER")]
+    pub after: PathBuf,
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(
+    about = "Combine note files into one delimited bundle",
+    long_about = "Concatenate note files, in the order given, with a bundle delimiter line between each one. Defaults to the first configured bundle delimiter, so `parse --bundle on` splits the result back into the same notes.\nExamples:\n  clinote merge visit1.txt visit2.txt --out bundle.txt\n  clinote merge notes/*.txt --out - --delimiter \"=== VISIT ===\"\n"
+)]
+pub struct MergeArgs {
+    #[arg(value_name = "INPUT", required = true)]
+    pub inputs: Vec<PathBuf>,
+    #[arg(long)]
+    pub out: PathBuf,
+    /// Delimiter line to use instead of the first `bundle.delimiters` entry.
+    #[arg(long)]
+    pub delimiter: Option<String>,
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
 }
 
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
+    logging::set_verbosity(Verbosity::from_flags(cli.quiet, cli.verbose));
     match cli.command {
         Commands::Parse(args) => run_parse(&args),
         Commands::Batch(args) => run_batch_command(&args),
@@ -166,12 +426,26 @@ pub fn run() -> Result<()> {
         Commands::Init(args) => run_init(&args),
         Commands::Demo(args) => run_demo(&args),
         Commands::Selftest(args) => run_selftest(&args),
+        Commands::Templates(args) => run_templates(&args),
+        Commands::Verify(args) => run_verify(&args),
+        Commands::Wordfreq(args) => run_wordfreq(&args),
+        Commands::Report(args) => run_report(&args),
+        Commands::ResolveHeading(args) => run_resolve_heading(&args),
+        Commands::Stats(args) => run_stats(&args),
+        Commands::Diff(args) => run_diff(&args),
+        Commands::Merge(args) => run_merge(&args),
     }
 }
 
 fn run_parse(args: &ParseArgs) -> Result<()> {
-    let config = Config::load(args.config.as_deref())?;
-    let input = util::read_to_string(&args.input)?;
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    if util::is_stdio(&args.out) && args.out_format.len() > 1 {
+        return Err(anyhow!("--out - accepts a single --out-format"));
+    }
+    if util::is_stdio(&args.input) && args.interactive {
+        return Err(anyhow!("--interactive cannot read the note from stdin"));
+    }
+    let input = util::read_input_with(&args.input, args.encoding.as_deref())?;
     let bundle_mode = args.bundle.unwrap_or(config.bundle.mode_default);
     let (note_texts, bundle_warnings) = parser::split_bundle(&input, bundle_mode, &config);
 
@@ -183,11 +457,33 @@ fn run_parse(args: &ParseArgs) -> Result<()> {
 
     let mut notes = Vec::new();
     for (idx, note_text) in note_texts.iter().enumerate() {
+        if args.explain {
+            let trace = parser::explain::explain_note(
+                note_text,
+                args.format,
+                &config,
+                ParseOptions {
+                    apply_heuristics,
+                    min_confidence: args.min_confidence,
+                },
+            );
+            // Keep stdout clean for the rendered notes when writing to `-`.
+            if util::is_stdio(&args.out) {
+                eprintln!("Note {}:", idx + 1);
+                eprint!("{}", parser::explain::format_trace(&trace));
+            } else {
+                println!("Note {}:", idx + 1);
+                print!("{}", parser::explain::format_trace(&trace));
+            }
+        }
         let (candidates, mut warnings) = parser::extract_candidates(
             note_text,
             args.format,
             &config,
-            ParseOptions { apply_heuristics },
+            ParseOptions {
+                apply_heuristics,
+                min_confidence: args.min_confidence,
+            },
         );
         warnings.extend(bundle_warnings.clone());
 
@@ -197,33 +493,80 @@ fn run_parse(args: &ParseArgs) -> Result<()> {
             candidates
         };
 
-        let note = parser::build_note(
+        let mut note = parser::build_note_with(
             selected,
             args.format,
             Some(args.input.display().to_string()),
             idx + 1,
             warnings,
+            &config.id_template,
         );
+        if args.sort_sections {
+            parser::sort_sections(&mut note, &config);
+        }
         notes.push(note);
     }
 
-    let rendered = render::render_notes(&notes, args.out_format, config.csv.layout)?;
-    util::write_string(&args.out, &rendered)?;
+    for out_format in &args.out_format {
+        let layout = csv_layout(&notes, &config);
+        let rendered = render::render_notes_with(&notes, *out_format, layout, &config)?;
+        let out_path = if args.out_format.len() == 1 {
+            args.out.clone()
+        } else {
+            args.out.with_extension(out_format.extension())
+        };
+        util::write_output_unless_dry_run(&out_path, &rendered, args.dry_run)?;
+    }
     Ok(())
 }
 
 fn run_batch_command(args: &BatchArgs) -> Result<()> {
-    let config = Config::load(args.config.as_deref())?;
+    let config = load_config(args.config.as_deref(), args.no_config)?;
     let report = run_batch(args, &config)?;
-    let report_path = args.out_dir.join("batch_report.json");
-    report.write_to(&report_path)?;
+    if args.dry_run {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.write_to(&args.out_dir.join("batch_report.json"))?;
+    }
+    util::write_output_unless_dry_run(
+        &args.out_dir.join("batch_report.csv"),
+        &report.to_csv()?,
+        args.dry_run,
+    )?;
+    if let Some(path) = &args.collect_unmapped {
+        let json = serde_json::to_string_pretty(&report.unmapped_ranking())?;
+        util::write_output_unless_dry_run(path, &json, args.dry_run)?;
+    }
+    if args.validate {
+        let validation = reports::BatchValidationReport {
+            template: batch_template(args),
+            files: report.validation.clone(),
+        };
+        util::write_output_unless_dry_run(
+            &args.out_dir.join("validation_report.json"),
+            &serde_json::to_string_pretty(&validation)?,
+            args.dry_run,
+        )?;
+    }
+    if args.combined {
+        util::write_output_unless_dry_run(
+            &args.out_dir.join("notes.json"),
+            &serde_json::to_string_pretty(&report.combined)?,
+            args.dry_run,
+        )?;
+    }
+    if exceeds_max_warnings(report.warnings_count, args.max_warnings) {
+        process::exit(3);
+    }
     Ok(())
 }
 
 pub fn run_batch(args: &BatchArgs, config: &Config) -> Result<BatchReport> {
     let start = Instant::now();
     let mut report = BatchReport::new("clinote");
-    std::fs::create_dir_all(&args.out_dir)?;
+    if !args.dry_run {
+        std::fs::create_dir_all(&args.out_dir)?;
+    }
 
     let glob_pattern = args
         .glob
@@ -237,21 +580,89 @@ pub fn run_batch(args: &BatchArgs, config: &Config) -> Result<BatchReport> {
 
     let bundle_mode = args.bundle.unwrap_or(config.bundle.mode_default);
 
-    for entry in glob(&pattern_str)? {
-        match entry {
-            Ok(path) => {
-                let file_result = process_file(&path, args, config, bundle_mode);
-                match file_result {
-                    Ok(notes) => {
-                        report.record_ok(&notes);
-                    }
-                    Err(err) => {
-                        report.record_failure(&path.display().to_string(), err.to_string());
-                    }
+    let mut paths = Vec::new();
+    for pattern in util::expand_braces(&pattern_str) {
+        for entry in glob(&pattern)? {
+            match entry {
+                Ok(path) => paths.push(path),
+                Err(err) => {
+                    report.record_failure("glob", err.to_string());
+                }
+            }
+        }
+    }
+    IgnoreList::load(&args.input_dir).retain(&mut paths);
+    paths.sort();
+    paths.dedup();
+
+    let selected: Vec<PathBuf> = paths
+        .into_iter()
+        .skip(args.skip)
+        .take(args.limit.unwrap_or(usize::MAX))
+        .collect();
+    let total = selected.len();
+    for (idx, path) in selected.into_iter().enumerate() {
+        let file_start = Instant::now();
+        let outcome = match args.timeout_ms {
+            Some(timeout_ms) => {
+                run_with_timeout(&path, idx + 1, args, config, bundle_mode, timeout_ms)
+            }
+            None => Some(read_and_process(&path, idx + 1, args, config, bundle_mode)),
+        };
+        report.record_timing(
+            &path.display().to_string(),
+            file_start.elapsed().as_millis(),
+        );
+        crate::verbose!(
+            "[{}/{}] {} ({} ms)",
+            idx + 1,
+            total,
+            path.display(),
+            file_start.elapsed().as_millis()
+        );
+        let Some((encoding, file_result)) = outcome else {
+            report.record_timeout(&path.display().to_string(), args.timeout_ms.unwrap_or(0));
+            continue;
+        };
+        if let Some(encoding) = encoding {
+            report.record_encoding(encoding);
+        }
+        let file_result = file_result.and_then(|file| {
+            for (out_path, rendered) in &file.outputs {
+                util::write_output_unless_dry_run(out_path, rendered, args.dry_run)?;
+            }
+            if args.validate {
+                report.record_validation(reports::FileValidation {
+                    file: path.display().to_string(),
+                    notes: file.issues,
+                });
+            }
+            Ok(file.notes)
+        });
+        match file_result {
+            Ok(notes)
+                if args.strict_structure
+                    && BatchReport::narrative_only_with(&notes, &config.fallback_section_name) =>
+            {
+                report.narrative_only_files += 1;
+                report.record_failure(
+                    &path.display().to_string(),
+                    format!(
+                        "No structure detected; content collapsed to {}",
+                        config.fallback_section_name
+                    ),
+                );
+            }
+            Ok(notes) => {
+                report.record_ok_with(&notes, &config.fallback_section_name);
+                if args.combined {
+                    report
+                        .combined
+                        .extend(sorted_for_output(&notes, args, config).into_owned());
                 }
             }
             Err(err) => {
-                report.record_failure("glob", err.to_string());
+                report.record_failure(&path.display().to_string(), err.to_string());
             }
         }
     }
@@ -261,15 +672,97 @@ pub fn run_batch(args: &BatchArgs, config: &Config) -> Result<BatchReport> {
     Ok(report)
 }
 
+/// One parsed file: its notes, validation issues, and the rendered outputs
+/// still to be written, so only the batch loop touches the output directory.
+struct ProcessedFile {
+    notes: Vec<crate::models::StructuredNote>,
+    issues: Vec<reports::NoteIssues>,
+    outputs: Vec<(PathBuf, String)>,
+}
+
+type FileOutcome = (Option<&'static str>, Result<ProcessedFile>);
+
+/// The notes as written out: with `--sort-sections`, a reordered copy with
+/// placeholders. Reports, validation and `--strict-structure` use the notes as
+/// detected, so placeholders neither hide missing sections nor count as found.
+fn sorted_for_output<'a>(
+    notes: &'a [crate::models::StructuredNote],
+    args: &BatchArgs,
+    config: &Config,
+) -> Cow<'a, [crate::models::StructuredNote]> {
+    if !args.sort_sections {
+        return Cow::Borrowed(notes);
+    }
+    let rules = validate::TemplateRules::builtin_with(batch_template(args), config);
+    let mut sorted = notes.to_vec();
+    for note in &mut sorted {
+        parser::sort_sections_with(note, &rules, config);
+    }
+    Cow::Owned(sorted)
+}
+
+fn batch_template(args: &BatchArgs) -> Template {
+    args.template
+        .unwrap_or_else(|| Template::from_format(args.format))
+}
+
+fn read_and_process(
+    path: &Path,
+    file_index: usize,
+    args: &BatchArgs,
+    config: &Config,
+    bundle_mode: BundleMode,
+) -> FileOutcome {
+    match util::read_bytes(path) {
+        Ok(bytes) => (
+            Some(util::detect_encoding(&bytes)),
+            process_file(path, file_index, &bytes, args, config, bundle_mode),
+        ),
+        Err(err) => (None, Err(err)),
+    }
+}
+
+/// Runs one file on a worker thread and gives up after `timeout_ms`. A timed
+/// out worker is abandoned rather than cancelled, but it only renders; its
+/// outputs are dropped unwritten. `None` means the limit was hit.
+fn run_with_timeout(
+    path: &Path,
+    file_index: usize,
+    args: &BatchArgs,
+    config: &Config,
+    bundle_mode: BundleMode,
+    timeout_ms: u64,
+) -> Option<FileOutcome> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let (path, args, config) = (path.to_path_buf(), args.clone(), config.clone());
+    std::thread::spawn(move || {
+        let _ = tx.send(read_and_process(
+            &path,
+            file_index,
+            &args,
+            &config,
+            bundle_mode,
+        ));
+    });
+    match rx.recv_timeout(std::time::Duration::from_millis(timeout_ms)) {
+        Ok(outcome) => Some(outcome),
+        Err(RecvTimeoutError::Timeout) => None,
+        Err(RecvTimeoutError::Disconnected) => Some((None, Err(anyhow!("worker panicked")))),
+    }
+}
+
 fn process_file(
     path: &Path,
+    file_index: usize,
+    bytes: &[u8],
     args: &BatchArgs,
     config: &Config,
     bundle_mode: BundleMode,
-) -> Result<Vec<crate::models::StructuredNote>> {
-    let content = util::read_to_string(path)?;
+) -> Result<ProcessedFile> {
+    let content = util::decode_text_with(bytes, args.encoding.as_deref())?;
     let (note_texts, bundle_warnings) = parser::split_bundle(&content, bundle_mode, config);
     let mut notes = Vec::new();
+    let mut issues = Vec::new();
     for (idx, note_text) in note_texts.iter().enumerate() {
         let (candidates, mut warnings) = parser::extract_candidates(
             note_text,
@@ -277,26 +770,57 @@ fn process_file(
             config,
             ParseOptions {
                 apply_heuristics: config.enable_fallback_heuristics,
+                min_confidence: args.min_confidence,
             },
         );
         warnings.extend(bundle_warnings.clone());
-        let note = parser::build_note(
+        let note = parser::build_note_with(
             candidates,
             args.format,
             Some(path.display().to_string()),
             idx + 1,
             warnings,
+            &config.id_template,
         );
+        if args.validate {
+            issues.push(reports::NoteIssues {
+                note_index: note.note_index,
+                issues: validate::validate_parsed_note(
+                    &note,
+                    note_text,
+                    batch_template(args),
+                    false,
+                    config,
+                ),
+            });
+        }
         notes.push(note);
     }
-
-    let rendered = render::render_notes(&notes, args.out_format, config.csv.layout)?;
-    let stem = util::file_stem(path);
-    let out_path = args
-        .out_dir
-        .join(format!("{}.{}", stem, args.out_format.extension()));
-    util::write_string(&out_path, &rendered)?;
-    Ok(notes)
+    // run_batch records these as failures, so don't leave outputs behind.
+    let unstructured = args.strict_structure
+        && BatchReport::narrative_only_with(&notes, &config.fallback_section_name);
+    let mut outputs = Vec::new();
+    if !args.summary_only && !unstructured {
+        let stem = util::file_stem(path);
+        let sorted = sorted_for_output(&notes, args, config);
+        for out_format in &args.out_format {
+            let layout = csv_layout(&sorted, config);
+            let rendered = render::render_notes_with(&sorted, *out_format, layout, config)?;
+            let out_path = args.out_dir.join(util::expand_out_template(
+                &args.out_template,
+                &stem,
+                args.format.as_str(),
+                file_index,
+                out_format.extension(),
+            ));
+            outputs.push((out_path, rendered));
+        }
+    }
+    Ok(ProcessedFile {
+        notes,
+        issues,
+        outputs,
+    })
 }
 
 fn run_sample(args: &SampleArgs) -> Result<()> {
@@ -304,63 +828,91 @@ fn run_sample(args: &SampleArgs) -> Result<()> {
 }
 
 fn run_validate(args: &ValidateArgs) -> Result<()> {
-    if let Some(input) = &args.input {
-        let template = args.template.unwrap_or(Template::Soap);
-        let config = Config::load(args.config.as_deref())?;
-        let input_text = util::read_to_string(input)?;
-        let (note_texts, bundle_warnings) =
-            parser::split_bundle(&input_text, config.bundle.mode_default, &config);
-        let mut reports = Vec::new();
-        let mut has_error = false;
-
-        for (idx, note_text) in note_texts.iter().enumerate() {
-            let (candidates, mut warnings) = parser::extract_candidates(
-                note_text,
-                template_to_format(template),
+    if !args.input.is_empty() {
+        let mut config = load_config(args.config.as_deref(), args.no_config)?;
+        if args.no_unknown_sections {
+            config.validation.forbid_unknown_sections = true;
+        }
+        let rules = match &args.template_name {
+            Some(name) => {
+                let custom = config.custom_template(name).ok_or_else(|| {
+                    anyhow!("Unknown template '{}'; define it under [[templates]]", name)
+                })?;
+                validate::TemplateRules::custom(custom)
+            }
+            None => validate::TemplateRules::builtin_with(
+                args.template.unwrap_or(Template::Soap),
                 &config,
-                ParseOptions {
-                    apply_heuristics: config.enable_fallback_heuristics,
-                },
-            );
-            warnings.extend(bundle_warnings.clone());
-            let note = parser::build_note(
-                candidates,
-                template_to_format(template),
-                Some(input.display().to_string()),
-                idx + 1,
-                warnings,
+            ),
+        };
+        let mut files = Vec::new();
+        for input in &args.input {
+            files.push(
+                validate_file(input, &rules, args.strict, &config).unwrap_or_else(|err| {
+                    ValidationSummary::unreadable(input, &rules, args.strict, &err)
+                }),
             );
-            let issues = validate::validate_note(&note, template, args.strict);
-            if issues.iter().any(|i| i.severity == Severity::Error) {
-                has_error = true;
-            }
-            reports.push(ValidationReport {
-                note_index: idx + 1,
-                issues,
-            });
         }
+        let has_read_error = files.iter().any(|file| file.error.is_some());
+        let has_error = files.iter().any(|file| {
+            file.reports
+                .iter()
+                .flat_map(|r| &r.issues)
+                .any(|i| i.severity == Severity::Error)
+        });
+        let warning_count = files
+            .iter()
+            .flat_map(|file| &file.reports)
+            .flat_map(|r| &r.issues)
+            .filter(|i| i.severity == Severity::Warn)
+            .count();
 
         if args.json {
-            let payload = ValidationSummary {
-                input: input.display().to_string(),
-                template,
+            // One shape however many inputs, so callers never branch on it.
+            let payload = MultiValidationSummary {
+                template: rules.name.clone(),
                 strict: args.strict,
-                reports,
+                has_warnings: warning_count > 0,
+                files,
             };
             println!("{}", serde_json::to_string_pretty(&payload)?);
+        } else if files.len() == 1 {
+            print_validation_file(&files[0]);
         } else {
-            print_validation_text(&reports);
+            for (idx, file) in files.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                println!("== {} ==", file.input);
+                print_validation_file(file);
+            }
         }
 
+        if has_read_error {
+            process::exit(1);
+        }
         if has_error {
             process::exit(2);
         }
+        if args.fail_on_warning && warning_count > 0 {
+            process::exit(3);
+        }
+        if exceeds_max_warnings(warning_count, args.max_warnings) {
+            process::exit(3);
+        }
         return Ok(());
     }
 
     if let Some(config_path) = &args.config {
         let config = Config::load(Some(config_path))?;
         println!("{}", config.summary());
+        let issues = validate::validate_config(&config);
+        if !issues.is_empty() {
+            println!("Config issues:");
+            for issue in &issues {
+                println!("- {:?}: {}", issue.severity, issue.message);
+            }
+        }
         return Ok(());
     }
 
@@ -370,52 +922,74 @@ fn run_validate(args: &ValidateArgs) -> Result<()> {
 }
 
 fn run_preview(args: &PreviewArgs) -> Result<()> {
-    let config = Config::load(args.config.as_deref())?;
+    let config = load_config(args.config.as_deref(), args.no_config)?;
     let template = args.template.unwrap_or(Template::Soap);
     let input_text = util::read_to_string(&args.input)?;
-    let (note_texts, _warnings) =
-        parser::split_bundle(&input_text, config.bundle.mode_default, &config);
+    let notes = parser::parse_notes(
+        &input_text,
+        template_to_format(template),
+        &config,
+        Some(args.input.display().to_string()),
+        0,
+        ParseOptions {
+            apply_heuristics: config.enable_fallback_heuristics,
+            min_confidence: 0.0,
+        },
+    );
 
-    for (idx, note_text) in note_texts.iter().enumerate() {
-        let (candidates, _) = parser::extract_candidates(
-            note_text,
-            template_to_format(template),
-            &config,
-            ParseOptions {
-                apply_heuristics: config.enable_fallback_heuristics,
-            },
-        );
-        let note = parser::build_note(
-            candidates,
-            template_to_format(template),
-            Some(args.input.display().to_string()),
-            idx + 1,
-            Vec::new(),
-        );
+    let mut previews = Vec::new();
+    let note_count = notes.len();
+    for (idx, note) in notes.into_iter().enumerate() {
+        let preview = PreviewReport {
+            note_index: note.note_index,
+            sections: validate::summarize_sections(&note),
+            warnings: note.warnings,
+        };
+        if args.json {
+            previews.push(preview);
+            continue;
+        }
         println!("Note {}:", idx + 1);
-        for summary in validate::summarize_sections(&note) {
+        for summary in &preview.sections {
             println!(
-                "- {}: {} lines, {} chars",
-                summary.name, summary.line_count, summary.char_count
+                "- {}: {} lines, {} chars, {} words, grade {:.1}",
+                summary.name,
+                summary.line_count,
+                summary.char_count,
+                summary.word_count,
+                summary.flesch_kincaid_grade
             );
         }
-        if idx + 1 < note_texts.len() {
+        print_parse_warnings(&preview.warnings);
+        if idx + 1 < note_count {
             println!();
         }
     }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&previews)?);
+    }
     Ok(())
 }
 
 fn run_init(args: &InitArgs) -> Result<()> {
+    if args.dry_run {
+        let template = if args.json {
+            config::default_config_json()?
+        } else {
+            config::default_template(args.full)?
+        };
+        print!("{}", template);
+        return Ok(());
+    }
     if args.path.exists() {
         return Err(anyhow!(
             "Config file already exists at {}",
             args.path.display()
         ));
     }
-    let template = default_config_template();
+    let template = config::default_template(args.full)?;
     util::write_string(&args.path, &template)?;
-    println!("Created default config at {}", args.path.display());
+    crate::info!("Created default config at {}", args.path.display());
     Ok(())
 }
 
@@ -445,33 +1019,48 @@ fn run_demo(args: &DemoArgs) -> Result<()> {
             &config,
             ParseOptions {
                 apply_heuristics: config.enable_fallback_heuristics,
+                min_confidence: 0.0,
             },
         );
-        let note = parser::build_note(
+        let note = parser::build_note_with(
             candidates,
             template_to_format(format),
             Some(path.display().to_string()),
             1,
             Vec::new(),
+            &config.id_template,
         );
-        let rendered =
-            render::render_notes(&[note.clone()], OutputFormat::Json, config.csv.layout)?;
+        let rendered = render::render_notes(
+            std::slice::from_ref(&note),
+            OutputFormat::Json,
+            config.csv.layout,
+        )?;
         let out_path = outputs_dir.join(format!("{}.json", util::file_stem(&path)));
         util::write_string(&out_path, &rendered)?;
 
-        let issues = validate::validate_note(&note, format, false);
+        let issues = validate::validate_note_rules(
+            &note,
+            &validate::TemplateRules::builtin_with(format, &config),
+            false,
+            &config.validation,
+        );
         let report_path = outputs_dir.join(format!("{}.validation.json", util::file_stem(&path)));
         util::write_string(&report_path, &serde_json::to_string_pretty(&issues)?)?;
     }
 
-    println!("Demo outputs written to {}", outputs_dir.display());
+    crate::info!("Demo outputs written to {}", outputs_dir.display());
     Ok(())
 }
 
 fn run_selftest(args: &SelftestArgs) -> Result<()> {
     let template = args.template.unwrap_or(Template::Soap);
     let out_dir = args.out.as_deref();
-    let summary = selftest::run_selftest(&args.fixtures, template, args.strict, out_dir)?;
+    let options = selftest::SelftestOptions {
+        extensions: args.ext.clone(),
+        failures_only: args.failures_only,
+    };
+    let summary =
+        selftest::run_selftest_with(&args.fixtures, template, args.strict, out_dir, &options)?;
 
     if args.json {
         println!("{}", serde_json::to_string_pretty(&summary)?);
@@ -485,14 +1074,216 @@ fn run_selftest(args: &SelftestArgs) -> Result<()> {
     if summary.total_errors > 0 {
         process::exit(2);
     }
+    if exceeds_max_warnings(summary.total_warnings, args.max_warnings) {
+        process::exit(3);
+    }
+    Ok(())
+}
+
+fn run_templates(args: &TemplatesArgs) -> Result<()> {
+    let specs = validate::template_specs();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&specs)?);
+        return Ok(());
+    }
+    for spec in &specs {
+        println!("{}:", spec.name);
+        println!("  Required:");
+        for group in &spec.required {
+            let name = group.first().cloned().unwrap_or_default();
+            if group.len() > 1 {
+                println!("  - {} (also: {})", name, group[1..].join(", "));
+            } else {
+                println!("  - {}", name);
+            }
+        }
+        println!("  Optional: {}", spec.optional.join(", "));
+    }
     Ok(())
 }
 
+fn run_verify(args: &VerifyArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    let template = args.template.unwrap_or(Template::Soap);
+    let input_text = util::read_to_string(&args.input)?;
+    let report = verify::verify_document(
+        &input_text,
+        template,
+        &config,
+        &args.input.display().to_string(),
+    );
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", verify::summarize_text(&report));
+    }
+
+    if report.dropped_count() > 0 {
+        process::exit(2);
+    }
+    Ok(())
+}
+
+fn run_report(args: &ReportArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    let template = args.template.unwrap_or(Template::Soap);
+    let input_text = util::read_to_string(&args.input)?;
+    let report = reports::document_report(
+        &input_text,
+        template,
+        args.strict,
+        &config,
+        &args.input.display().to_string(),
+    );
+    util::write_string(&args.out, &serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
+
+fn run_resolve_heading(args: &ResolveHeadingArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    let resolution = parser::headings::resolve_heading_for(&args.heading, &config, args.format);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&resolution)?);
+        return Ok(());
+    }
+    match (&resolution.canonical, &resolution.alias) {
+        (Some(name), Some(alias)) => println!("{} (alias '{}')", name, alias),
+        (Some(name), None) => println!("{} ({})", name, resolution.method),
+        (None, _) => println!("'{}' is unmapped", resolution.input),
+    }
+    Ok(())
+}
+
+fn run_wordfreq(args: &WordfreqArgs) -> Result<()> {
+    let notes = wordfreq::load_notes(&args.input_dir)?;
+    let mut words = wordfreq::word_frequencies(&notes, &args.section, args.min_count);
+    words.truncate(args.top);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&words)?);
+    } else {
+        for word in &words {
+            println!("{}\t{}", word.count, word.token);
+        }
+    }
+    Ok(())
+}
+
+fn run_diff(args: &DiffArgs) -> Result<()> {
+    let report = diff::diff_paths(&args.before, &args.after)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", diff::format_summary(&report));
+    }
+    Ok(())
+}
+
+fn run_merge(args: &MergeArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    let delimiter = args
+        .delimiter
+        .clone()
+        .or_else(|| config.bundle.delimiters.first().cloned())
+        .filter(|delimiter| !delimiter.trim().is_empty())
+        .ok_or_else(|| anyhow!("No bundle delimiter configured; pass --delimiter"))?;
+    let mut notes = Vec::new();
+    for input in &args.inputs {
+        notes.push(util::read_to_string(input)?);
+    }
+    util::write_output(&args.out, &parser::merge_bundle(&notes, &delimiter))?;
+    if !util::is_stdio(&args.out) {
+        crate::info!("Merged {} notes into {}", notes.len(), args.out.display());
+    }
+    Ok(())
+}
+
+fn run_stats(args: &StatsArgs) -> Result<()> {
+    let config = load_config(args.config.as_deref(), args.no_config)?;
+    let glob_pattern = args
+        .glob
+        .clone()
+        .unwrap_or_else(|| config.glob_default.clone());
+    let pattern = args.input_dir.join(glob_pattern);
+    let pattern_str = pattern
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid glob pattern"))?
+        .to_string();
+    let mut paths = Vec::new();
+    for pattern in util::expand_braces(&pattern_str) {
+        for entry in glob(&pattern)? {
+            paths.push(entry?);
+        }
+    }
+    IgnoreList::load(&args.input_dir).retain(&mut paths);
+    paths.sort();
+    paths.dedup();
+
+    let mut texts = Vec::new();
+    for path in &paths {
+        texts.push(util::read_to_string(path)?);
+    }
+    let stats = stats::corpus_stats(&texts, args.format, &config);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{}", stats::format_table(&stats));
+    }
+    Ok(())
+}
+
+fn load_config(path: Option<&Path>, no_config: bool) -> Result<Config> {
+    if no_config {
+        Ok(Config::default())
+    } else {
+        Config::load(path)
+    }
+}
+
+fn csv_layout(notes: &[crate::models::StructuredNote], config: &Config) -> CsvLayout {
+    render::csv::resolve_layout(notes, &config.csv).unwrap_or_else(|| {
+        eprintln!("Warning: notes mix formats; using global CSV layout");
+        config.csv.layout
+    })
+}
+
 fn template_to_format(template: Template) -> NoteFormat {
     match template {
         Template::Soap => NoteFormat::Soap,
         Template::Hp => NoteFormat::Hp,
         Template::Discharge => NoteFormat::Discharge,
+        Template::Progress => NoteFormat::Progress,
+        Template::Operative => NoteFormat::Operative,
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct PreviewReport {
+    note_index: usize,
+    sections: Vec<validate::SectionSummary>,
+    warnings: Vec<ParseWarning>,
+}
+
+/// Prints parser warnings grouped by severity, most severe first.
+fn print_parse_warnings(warnings: &[ParseWarning]) {
+    for (severity, label) in [
+        (WarningSeverity::Error, "Errors"),
+        (WarningSeverity::Warning, "Warnings"),
+        (WarningSeverity::Info, "Info"),
+    ] {
+        let group: Vec<&ParseWarning> =
+            warnings.iter().filter(|w| w.severity == severity).collect();
+        if group.is_empty() {
+            continue;
+        }
+        println!("{}:", label);
+        for warning in group {
+            println!(
+                "- lines {}-{}: {} ({})",
+                warning.line_start, warning.line_end, warning.message, warning.code
+            );
+        }
     }
 }
 
@@ -500,19 +1291,108 @@ fn template_to_format(template: Template) -> NoteFormat {
 struct ValidationReport {
     note_index: usize,
     issues: Vec<ValidationIssue>,
+    warnings: Vec<ParseWarning>,
 }
 
 #[derive(Debug, serde::Serialize)]
 struct ValidationSummary {
     input: String,
-    template: Template,
+    template: String,
     strict: bool,
+    has_warnings: bool,
     reports: Vec<ValidationReport>,
+    /// Why the input could not be read; `reports` is empty when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ValidationSummary {
+    fn unreadable(
+        input: &Path,
+        rules: &validate::TemplateRules,
+        strict: bool,
+        err: &anyhow::Error,
+    ) -> Self {
+        ValidationSummary {
+            input: input.display().to_string(),
+            template: rules.name.clone(),
+            strict,
+            has_warnings: false,
+            reports: Vec::new(),
+            error: Some(format!("{:#}", err)),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct MultiValidationSummary {
+    template: String,
+    strict: bool,
+    has_warnings: bool,
+    files: Vec<ValidationSummary>,
+}
+
+fn validate_file(
+    input: &Path,
+    rules: &validate::TemplateRules,
+    strict: bool,
+    config: &Config,
+) -> Result<ValidationSummary> {
+    let input_text = util::read_to_string(input)?;
+    let reports: Vec<ValidationReport> = validate::validate_notes_rules(
+        &input_text,
+        rules,
+        strict,
+        config,
+        Some(input.display().to_string()),
+    )
+    .into_iter()
+    .map(|(note, issues)| ValidationReport {
+        note_index: note.note_index,
+        issues,
+        warnings: note.warnings,
+    })
+    .collect();
+    Ok(ValidationSummary {
+        input: input.display().to_string(),
+        template: rules.name.clone(),
+        strict,
+        has_warnings: reports
+            .iter()
+            .flat_map(|r| &r.issues)
+            .any(|i| i.severity == Severity::Warn),
+        reports,
+        error: None,
+    })
+}
+
+fn print_validation_file(file: &ValidationSummary) {
+    match &file.error {
+        Some(err) => eprintln!("Error: {}", err),
+        None => print_validation_text(&file.reports),
+    }
+}
+
+/// Reports on stderr when `count` is over the `--max-warnings` ceiling.
+fn exceeds_max_warnings(count: usize, max_warnings: Option<usize>) -> bool {
+    match max_warnings {
+        Some(max) if count > max => {
+            eprintln!("{} warnings exceed --max-warnings {}", count, max);
+            true
+        }
+        _ => false,
+    }
 }
 
 fn print_validation_text(reports: &[ValidationReport]) {
     for report in reports {
         println!("Note {}:", report.note_index);
+        for warning in &report.warnings {
+            println!(
+                "  - {:?} (parser): {} [lines {}-{}]",
+                warning.severity, warning.message, warning.line_start, warning.line_end
+            );
+        }
         if report.issues.is_empty() {
             println!("  No issues detected.");
             continue;
@@ -527,52 +1407,3 @@ fn print_validation_text(reports: &[ValidationReport]) {
         }
     }
 }
-
-fn default_config_template() -> String {
-    let template = r#"# Clinote config template
-# Customize section orders, aliases, and bundle delimiters.
-
-[formats.soap]
-section_order = ["Subjective", "Objective", "Assessment", "Plan"]
-
-[formats.hp]
-section_order = [
-  "Chief Complaint",
-  "HPI",
-  "PMH",
-  "Medications",
-  "Allergies",
-  "ROS",
-  "Physical Exam",
-  "Assessment",
-  "Plan"
-]
-
-[formats.discharge]
-section_order = [
-  "Admission Dx",
-  "Discharge Dx",
-  "Hospital Course",
-  "Medications",
-  "Follow-up",
-  "Disposition",
-  "Instructions"
-]
-
-# Map variants to canonical headings.
-heading_aliases = { "Hx" = "PMH", "Dx" = "Assessment" }
-
-# Enable heuristic fallbacks for missing headings.
-enable_fallback_heuristics = true
-
-[bundle]
-mode_default = "auto"
-delimiters = ["----- NOTE -----", "=== VISIT ==="]
-
-[csv]
-layout = "wide"
-
-glob_default = "*.txt"
-"#;
-    template.to_string()
-}
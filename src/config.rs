@@ -1,7 +1,13 @@
-use crate::models::{BundleMode, CsvLayout, NoteFormat, SectionName};
+use crate::models::{
+    BundleMode, CsvLayout, DuplicatePolicy, HeadingMatching, NoteFormat, SectionName, Severity,
+};
 use crate::util;
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
+use directories::BaseDirs;
+use regex::Regex;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::{Error as _, IntoDeserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -17,8 +23,42 @@ pub struct Config {
     pub bundle: BundleConfig,
     #[serde(default)]
     pub csv: CsvConfig,
+    #[serde(default)]
+    pub markdown: MarkdownConfig,
+    #[serde(default)]
+    pub json: JsonConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub parser: ParserConfig,
+    #[serde(default)]
+    pub normalize: NormalizeConfig,
     #[serde(default = "default_glob")]
     pub glob_default: String,
+    /// Section name for text with no headings and for unmapped headings.
+    #[serde(default = "default_fallback_section_name")]
+    pub fallback_section_name: String,
+    /// Note id pattern with `{index}`, `{stem}`, `{timestamp}` and `{format}`.
+    #[serde(default = "default_id_template")]
+    pub id_template: String,
+    /// Validation templates for note types without a built-in `Template`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<CustomTemplate>,
+}
+
+/// A `[[templates]]` entry, selected with `clinote validate --template-name`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomTemplate {
+    pub name: String,
+    /// Format whose `section_order` and aliases are used to parse the note.
+    #[serde(default = "default_custom_template_format")]
+    pub format: NoteFormat,
+    /// Each entry is a section name, or a list of names any one of which
+    /// satisfies the requirement.
+    #[serde(default, deserialize_with = "deserialize_required_sections")]
+    pub required_sections: Vec<Vec<String>>,
+    #[serde(default)]
+    pub optional_sections: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,32 +66,467 @@ pub struct FormatsConfig {
     pub soap: FormatSpec,
     pub hp: FormatSpec,
     pub discharge: FormatSpec,
+    #[serde(default = "default_progress_format")]
+    pub progress: FormatSpec,
+    #[serde(default = "default_operative_format")]
+    pub operative: FormatSpec,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FormatSpec {
-    pub section_order: Vec<SectionName>,
+    #[serde(deserialize_with = "deserialize_section_order")]
+    pub section_order: Vec<String>,
+    /// Aliases that apply only to this format, consulted before the global
+    /// `heading_aliases`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub heading_aliases: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleConfig {
     pub mode_default: BundleMode,
     pub delimiters: Vec<String>,
+    /// Strip leading banner lines shared by most notes after a split.
+    #[serde(default)]
+    pub strip_banners: bool,
+    /// Share of notes (0.0-1.0) a leading line must appear in to count as a banner.
+    #[serde(default = "default_banner_threshold")]
+    pub banner_threshold: f32,
+    /// Match delimiter lines exactly (after trimming) instead of ignoring case
+    /// and runs of whitespace.
+    #[serde(default)]
+    pub case_sensitive_delimiters: bool,
+    /// Regexes for a line that starts a new note when no delimiter is found.
+    #[serde(default = "default_bundle_date_patterns")]
+    pub date_patterns: Vec<String>,
+    /// Date lines needed before a file is split on them.
+    #[serde(default = "default_min_dates_to_split")]
+    pub min_dates_to_split: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CsvConfig {
     pub layout: CsvLayout,
+    #[serde(default)]
+    pub by_format: HashMap<NoteFormat, CsvLayout>,
+    /// Wide-layout cell value for a section the note does not have, so it can
+    /// be told apart from a detected but empty section.
+    #[serde(default)]
+    pub missing_marker: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownConfig {
+    #[serde(default = "default_note_separator")]
+    pub note_separator: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonConfig {
+    /// Wrap JSON output as `{"schema_version": ..., "notes": [...]}`.
+    #[serde(default)]
+    pub wrap_envelope: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserConfig {
+    #[serde(default = "default_fallback_separators")]
+    pub fallback_separators: Vec<String>,
+    #[serde(default = "default_duplicate_policy")]
+    pub duplicate_policy: DuplicatePolicy,
+    /// Alias for `duplicate_policy = "merge"`; when set, `duplicate_policy` is
+    /// ignored.
+    #[serde(default)]
+    pub merge_duplicate_sections: bool,
+    #[serde(default)]
+    pub narrative_chunk_chars: Option<usize>,
+    #[serde(default = "default_heading_matching")]
+    pub heading_matching: HeadingMatching,
+    #[serde(default)]
+    pub preserve_heading_case: bool,
+    /// `Key: value` labels that make up a leading demographics header, which
+    /// is skipped during heading detection. An empty list disables it.
+    #[serde(default = "default_demographic_fields")]
+    pub demographic_fields: Vec<String>,
+    /// Extract vital signs from Objective sections into `Section::vitals`.
+    #[serde(default)]
+    pub parse_vitals: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NormalizeConfig {
+    /// Rejoin hard-wrapped prose lines within each section.
+    #[serde(default)]
+    pub rewrap: bool,
+    /// Keep each section's un-normalized source lines as `raw_content`.
+    #[serde(default)]
+    pub keep_raw_content: bool,
+    /// Keep `*` and `\u{2022}` bullet markers instead of rewriting them to `-`.
+    #[serde(default)]
+    pub preserve_bullets: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    #[serde(default)]
+    pub forbid_unknown_sections: bool,
+    /// Regexes for a date or encounter marker; notes matching none get
+    /// `no_encounter_date`. An empty list turns the check off.
+    #[serde(default = "default_date_patterns")]
+    pub date_patterns: Vec<String>,
+    /// Minimum word count per section name; sections below it get
+    /// `section_too_few_words`.
+    #[serde(default)]
+    pub min_words_by_section: HashMap<String, usize>,
+    /// Boilerplate that leaves a required section effectively empty, matched
+    /// case-insensitively against its whole trimmed content.
+    #[serde(default = "default_placeholder_phrases")]
+    pub placeholder_phrases: Vec<String>,
+    /// Sections with fewer trimmed characters get `section_too_short`; 0 turns
+    /// the check off.
+    #[serde(default = "default_min_section_len")]
+    pub min_section_len: usize,
+    /// Required sections with confidence below this (e.g. 0.6 for fallback
+    /// headings) get `low_confidence_section`; 0.0 turns the check off.
+    #[serde(default = "default_confidence_warn")]
+    pub confidence_warn: f32,
+    /// Regex checks on section content (`[[validation.rules]]`), run after
+    /// the built-in checks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ContentRule>,
+}
+
+/// Flags a section whose content lacks (`must_match = true`) or contains
+/// (`must_match = false`) a match for `pattern`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentRule {
+    pub section: String,
+    pub pattern: String,
+    #[serde(default = "default_rule_severity")]
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    #[serde(default = "default_true")]
+    pub must_match: bool,
+}
+
+/// Accepts any section name; built-in names and their aliases (e.g. `S`,
+/// `Meds`) resolve to the canonical `SectionName` spelling.
+fn deserialize_section_order<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Vec::<String>::deserialize(deserializer)?;
+    raw.into_iter()
+        .map(|name| {
+            if name.trim().is_empty() {
+                return Err(D::Error::custom("section names must not be empty"));
+            }
+            Ok(canonical_section_name(&name))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RequiredSection {
+    One(String),
+    AnyOf(Vec<String>),
+}
+
+fn deserialize_required_sections<'de, D>(deserializer: D) -> Result<Vec<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Vec::<RequiredSection>::deserialize(deserializer)?;
+    raw.into_iter()
+        .map(|entry| {
+            let names = match entry {
+                RequiredSection::One(name) => vec![name],
+                RequiredSection::AnyOf(names) => names,
+            };
+            if names.is_empty() || names.iter().any(|name| name.trim().is_empty()) {
+                return Err(D::Error::custom("required section names must not be empty"));
+            }
+            Ok(names
+                .iter()
+                .map(|name| canonical_section_name(name))
+                .collect())
+        })
+        .collect()
+}
+
+pub fn canonical_section_name(name: &str) -> String {
+    let deserializer: StrDeserializer<'_, ValueError> = name.trim().into_deserializer();
+    match SectionName::deserialize(deserializer) {
+        Ok(builtin) => builtin.as_str().to_string(),
+        Err(_) => name.trim().to_string(),
+    }
+}
+
+/// Environment variable naming a config file, used when `--config` is not given.
+pub const CONFIG_ENV_VAR: &str = "CLINOTE_CONFIG";
+
+/// Comments `clinote init --full` places before and after the matching line
+/// of the serialized defaults.
+const FULL_TEMPLATE_NOTES: &[(&str, &str, &str)] = &[
+    (
+        "enable_fallback_heuristics",
+        "# Enable heuristic fallbacks (e.g. \"CC - chest pain\") when no headings are found.",
+        "",
+    ),
+    (
+        "glob_default",
+        "# File pattern used by `clinote batch` when --glob is not given.",
+        "",
+    ),
+    (
+        "fallback_section_name",
+        "# Section that holds text with no headings, and content under unmapped headings.",
+        "",
+    ),
+    (
+        "id_template",
+        "# Note id pattern; placeholders: {index}, {stem}, {timestamp}, {format}.",
+        "# Custom validation templates, used with `clinote validate --template-name`.\n# A required entry may list alternatives: [\"Plan\", \"Interventions\"].\n# [[templates]]\n# name = \"Nursing Assessment\"\n# format = \"hp\"\n# required_sections = [\"Assessment\", [\"Plan\", \"Interventions\"]]\n# optional_sections = [\"Allergies\"]",
+    ),
+    (
+        "[formats.soap]",
+        "# section_order accepts built-in names or custom ones such as \"Social History\".\n# Aliases for one format go in [formats.<format>.heading_aliases] and win\n# over the global [heading_aliases], e.g. \"Dx\" = \"Discharge Dx\" for discharge.",
+        "",
+    ),
+    (
+        "[heading_aliases]",
+        "# Map heading variants to canonical headings.",
+        "# \"Hx\" = \"PMH\"\n# \"Dx\" = \"Assessment\"",
+    ),
+    (
+        "[bundle]",
+        "# How multi-note files are split: \"auto\", \"on\", or \"off\".\n# strip_banners removes leading lines (e.g. an institutional header) found\n# in at least banner_threshold of the split notes.\n# Delimiters match regardless of case and repeated spaces unless\n# case_sensitive_delimiters = true.\n# Without delimiters, lines matching date_patterns start new notes once at\n# least min_dates_to_split of them are found, e.g. add '^\\s*\\d{2}\\.\\d{2}\\.\\d{4}'\n# for DD.MM.YYYY dates.",
+        "",
+    ),
+    (
+        "[csv]",
+        "# CSV layout: \"wide\" or \"long\".\n# missing_marker is written in wide cells for sections a note lacks.",
+        "",
+    ),
+    (
+        "[csv.by_format]",
+        "# Override the CSV layout per format.",
+        "# discharge = \"long\"",
+    ),
+    (
+        "[markdown]",
+        "# note_separator is placed between notes in Markdown output.",
+        "",
+    ),
+    (
+        "[json]",
+        "# wrap_envelope writes {\"schema_version\": \"1\", \"notes\": [...]} instead of a\n# bare note or array, so consumers can detect layout changes.",
+        "",
+    ),
+    (
+        "[validation]",
+        "# forbid_unknown_sections escalates unknown sections from info to warn\n# (or error under --strict).\n# date_patterns: regexes for a date or encounter id; notes matching none get a\n# no_encounter_date info issue. Set to [] to disable.\n# placeholder_phrases: required sections whose whole content is one of these\n# (case-insensitive) get a placeholder_content warning.\n# min_section_len: sections shorter than this many characters get a\n# section_too_short warning. Set to 0 to disable.\n# confidence_warn: required sections detected with lower confidence (fallback\n# headings score 0.6) get a low_confidence_section info issue (warn under\n# --strict). Set to 0.0 to disable.",
+        "",
+    ),
+    (
+        "[validation.min_words_by_section]",
+        "# Minimum word count for specific sections, e.g.\n# HPI = 10",
+        "# Regex rules on section content. must_match = true (the default) flags a\n# section without a match, false flags one with a match. severity is\n# \"info\", \"warn\" (default), or \"error\".\n# [[validation.rules]]\n# section = \"Plan\"\n# pattern = \"\\\\d+ (days|weeks)\"\n# code = \"plan_missing_follow_up\"\n# message = \"Plan has no follow-up interval\"",
+    ),
+    (
+        "[normalize]",
+        "# rewrap rejoins hard-wrapped prose lines into paragraphs, keeping bullets\n# and blank-line breaks.\n# keep_raw_content stores each section's verbatim source lines as raw_content.\n# preserve_bullets keeps `*` and `\u{2022}` bullets instead of rewriting them to `-`.",
+        "",
+    ),
+    (
+        "[parser]",
+        "# fallback_separators: separators recognized by fallback heuristics.\n# duplicate_policy: \"keep_all\", \"keep_highest\", or \"merge\" (one section\n# with the lowest confidence of its parts).\n# merge_duplicate_sections = true is an alias for duplicate_policy = \"merge\".\n# heading_matching: \"ascii\", \"unicode\" (Unicode case folding), or\n# \"unicode_no_accents\" (also strips diacritics).\n# preserve_heading_case: name sections with the heading as written (e.g.\n# \"ASSESSMENT\", \"Dx\"); ordering and validation still use canonical names.\n# demographic_fields: labels of the leading `Key: value` header lines that are\n# never treated as headings. Set to [] to disable.\n# parse_vitals: pull BP, HR, RR, temperature and SpO2 readings out of Objective\n# sections into a `vitals` map; the content itself is unchanged.",
+        "# Split heading-less notes longer than this many characters into numbered\n# Narrative parts on blank lines. Off when unset.\n# narrative_chunk_chars = 2000",
+    ),
+];
+
+/// Serializes `Config::default()` as TOML. With `full`, every option is
+/// annotated and unset options are shown as commented examples.
+pub fn default_template(full: bool) -> Result<String> {
+    let body = toml::to_string_pretty(&Config::default())?;
+    let mut out = String::from("# Clinote config generated from the built-in defaults.\n\n");
+    if !full {
+        out.push_str(&body);
+        return Ok(out);
+    }
+    for line in body.lines() {
+        let note = FULL_TEMPLATE_NOTES
+            .iter()
+            .find(|(key, _, _)| line == *key || line.starts_with(&format!("{} =", key)));
+        if let Some((_, before, _)) = note {
+            out.push_str(before);
+            out.push('\n');
+        }
+        out.push_str(line);
+        out.push('\n');
+        if let Some((_, _, after)) = note.filter(|(_, _, after)| !after.is_empty()) {
+            out.push_str(after);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// `Config::default()` as pretty-printed JSON, for `clinote init --dry-run --json`.
+pub fn default_config_json() -> Result<String> {
+    let mut out = serde_json::to_string_pretty(&Config::default())?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn section_names(names: &[SectionName]) -> Vec<String> {
+    names.iter().map(|name| name.as_str().to_string()).collect()
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_operative_format() -> FormatSpec {
+    FormatSpec {
+        section_order: section_names(&[
+            SectionName::PreopDx,
+            SectionName::PostopDx,
+            SectionName::Procedure,
+            SectionName::Findings,
+            SectionName::Ebl,
+            SectionName::Specimens,
+        ]),
+        heading_aliases: HashMap::new(),
+    }
+}
+
+fn default_progress_format() -> FormatSpec {
+    FormatSpec {
+        section_order: section_names(&[
+            SectionName::IntervalHistory,
+            SectionName::Events,
+            SectionName::Subjective,
+            SectionName::Objective,
+            SectionName::Assessment,
+            SectionName::Plan,
+        ]),
+        heading_aliases: HashMap::new(),
+    }
+}
+
 fn default_glob() -> String {
     "*.txt".to_string()
 }
 
+fn default_custom_template_format() -> NoteFormat {
+    NoteFormat::Soap
+}
+
+fn default_fallback_section_name() -> String {
+    "Narrative".to_string()
+}
+
+fn default_id_template() -> String {
+    util::DEFAULT_ID_TEMPLATE.to_string()
+}
+
+fn default_fallback_separators() -> Vec<String> {
+    vec![":".to_string(), "-".to_string()]
+}
+
+fn default_duplicate_policy() -> DuplicatePolicy {
+    DuplicatePolicy::KeepAll
+}
+
+fn default_demographic_fields() -> Vec<String> {
+    [
+        "Patient",
+        "Patient Name",
+        "Name",
+        "DOB",
+        "Date of Birth",
+        "Age",
+        "Sex",
+        "Gender",
+        "MRN",
+        "Service",
+        "Attending",
+        "Provider",
+        "Location",
+        "Room",
+        "Visit Date",
+        "Admit Date",
+        "Date of Service",
+    ]
+    .iter()
+    .map(|field| field.to_string())
+    .collect()
+}
+
+fn default_banner_threshold() -> f32 {
+    0.7
+}
+
+fn default_placeholder_phrases() -> Vec<String> {
+    [
+        "[See note]",
+        "See note",
+        "See above",
+        "N/A",
+        "NA",
+        "Deferred",
+        "TBD",
+        "---",
+    ]
+    .iter()
+    .map(|phrase| phrase.to_string())
+    .collect()
+}
+
+fn default_rule_severity() -> Severity {
+    Severity::Warn
+}
+
+fn default_min_section_len() -> usize {
+    20
+}
+
+fn default_confidence_warn() -> f32 {
+    0.7
+}
+
+fn default_bundle_date_patterns() -> Vec<String> {
+    vec![
+        r"^\s*\d{4}-\d{2}-\d{2}".to_string(),
+        r"^\s*\d{2}/\d{2}/\d{4}".to_string(),
+    ]
+}
+
+fn default_min_dates_to_split() -> usize {
+    2
+}
+
+fn default_date_patterns() -> Vec<String> {
+    vec![
+        r"\b\d{4}-\d{1,2}-\d{1,2}\b".to_string(),
+        r"\b\d{1,2}[/.-]\d{1,2}[/.-]\d{2,4}\b".to_string(),
+        r"(?i)\b(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.? \d{1,2},? \d{4}\b"
+            .to_string(),
+        r"(?i)\b(?:encounter|visit|csn)\s*(?:#|no\.?|id)?\s*:?\s*\d+".to_string(),
+    ]
+}
+
+fn default_heading_matching() -> HeadingMatching {
+    HeadingMatching::Ascii
+}
+
+fn default_note_separator() -> String {
+    crate::render::markdown::DEFAULT_NOTE_SEPARATOR.to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -60,7 +535,15 @@ impl Default for Config {
             enable_fallback_heuristics: true,
             bundle: BundleConfig::default(),
             csv: CsvConfig::default(),
+            markdown: MarkdownConfig::default(),
+            json: JsonConfig::default(),
+            validation: ValidationConfig::default(),
+            parser: ParserConfig::default(),
+            normalize: NormalizeConfig::default(),
             glob_default: default_glob(),
+            fallback_section_name: default_fallback_section_name(),
+            id_template: default_id_template(),
+            templates: Vec::new(),
         }
     }
 }
@@ -69,15 +552,16 @@ impl Default for FormatsConfig {
     fn default() -> Self {
         Self {
             soap: FormatSpec {
-                section_order: vec![
+                section_order: section_names(&[
                     SectionName::Subjective,
                     SectionName::Objective,
                     SectionName::Assessment,
                     SectionName::Plan,
-                ],
+                ]),
+                heading_aliases: HashMap::new(),
             },
             hp: FormatSpec {
-                section_order: vec![
+                section_order: section_names(&[
                     SectionName::ChiefComplaint,
                     SectionName::Hpi,
                     SectionName::Pmh,
@@ -87,10 +571,11 @@ impl Default for FormatsConfig {
                     SectionName::PhysicalExam,
                     SectionName::Assessment,
                     SectionName::Plan,
-                ],
+                ]),
+                heading_aliases: HashMap::new(),
             },
             discharge: FormatSpec {
-                section_order: vec![
+                section_order: section_names(&[
                     SectionName::AdmissionDx,
                     SectionName::DischargeDx,
                     SectionName::HospitalCourse,
@@ -98,8 +583,11 @@ impl Default for FormatsConfig {
                     SectionName::FollowUp,
                     SectionName::Disposition,
                     SectionName::Instructions,
-                ],
+                ]),
+                heading_aliases: HashMap::new(),
             },
+            progress: default_progress_format(),
+            operative: default_operative_format(),
         }
     }
 }
@@ -109,6 +597,11 @@ impl Default for BundleConfig {
         Self {
             mode_default: BundleMode::Auto,
             delimiters: vec!["----- NOTE -----".to_string(), "=== VISIT ===".to_string()],
+            strip_banners: false,
+            banner_threshold: default_banner_threshold(),
+            case_sensitive_delimiters: false,
+            date_patterns: default_bundle_date_patterns(),
+            min_dates_to_split: default_min_dates_to_split(),
         }
     }
 }
@@ -117,6 +610,56 @@ impl Default for CsvConfig {
     fn default() -> Self {
         Self {
             layout: CsvLayout::Wide,
+            by_format: HashMap::new(),
+            missing_marker: String::new(),
+        }
+    }
+}
+
+impl ParserConfig {
+    /// `duplicate_policy`, or `Merge` when `merge_duplicate_sections` is set.
+    pub fn effective_duplicate_policy(&self) -> DuplicatePolicy {
+        if self.merge_duplicate_sections {
+            DuplicatePolicy::Merge
+        } else {
+            self.duplicate_policy
+        }
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            fallback_separators: default_fallback_separators(),
+            duplicate_policy: default_duplicate_policy(),
+            merge_duplicate_sections: false,
+            narrative_chunk_chars: None,
+            heading_matching: default_heading_matching(),
+            preserve_heading_case: false,
+            demographic_fields: default_demographic_fields(),
+            parse_vitals: false,
+        }
+    }
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            forbid_unknown_sections: false,
+            date_patterns: default_date_patterns(),
+            min_words_by_section: HashMap::new(),
+            placeholder_phrases: default_placeholder_phrases(),
+            min_section_len: default_min_section_len(),
+            confidence_warn: default_confidence_warn(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            note_separator: default_note_separator(),
         }
     }
 }
@@ -124,38 +667,156 @@ impl Default for CsvConfig {
 impl Config {
     pub fn load(path: Option<&Path>) -> Result<Self> {
         let candidate = match path {
-            Some(path) => PathBuf::from(path),
-            None => PathBuf::from("clinote.toml"),
+            Some(path) => Some(PathBuf::from(path)),
+            None => match Self::env_path()? {
+                Some(path) => Some(path),
+                None => Self::search_paths().into_iter().find(|p| p.exists()),
+            },
         };
-        if candidate.exists() {
-            let content = fs::read_to_string(&candidate)?;
-            let config: Config = toml::from_str(&content).map_err(|err| {
-                anyhow!("Failed to parse config {}: {}", candidate.display(), err)
+        match candidate {
+            Some(candidate) if candidate.exists() => {
+                let content = fs::read_to_string(&candidate)?;
+                let config: Config = toml::from_str(&content).map_err(|err| {
+                    anyhow!("Failed to parse config {}: {}", candidate.display(), err)
+                })?;
+                config
+                    .check_rule_patterns()
+                    .map_err(|err| anyhow!("Invalid config {}: {}", candidate.display(), err))?;
+                Ok(config)
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Compiles each `[[validation.rules]]` pattern, so a typo fails the load
+    /// instead of silently disabling the rule.
+    pub fn check_rule_patterns(&self) -> Result<()> {
+        for rule in &self.validation.rules {
+            Regex::new(&rule.pattern).map_err(|err| {
+                anyhow!(
+                    "validation.rules entry '{}' has an invalid pattern '{}': {}",
+                    rule.code,
+                    rule.pattern,
+                    err
+                )
             })?;
-            Ok(config)
-        } else {
-            Ok(Config::default())
         }
+        Ok(())
     }
 
-    pub fn section_order(&self, format: NoteFormat) -> Vec<String> {
-        let list = match format {
-            NoteFormat::Soap => &self.formats.soap.section_order,
-            NoteFormat::Hp => &self.formats.hp.section_order,
-            NoteFormat::Discharge => &self.formats.discharge.section_order,
+    /// The config named by `CLINOTE_CONFIG`, if set. A path that doesn't exist
+    /// is an error rather than a silent fall back to defaults.
+    pub fn env_path() -> Result<Option<PathBuf>> {
+        let Some(value) = std::env::var_os(CONFIG_ENV_VAR).filter(|v| !v.is_empty()) else {
+            return Ok(None);
         };
-        list.iter().map(|s| s.as_str().to_string()).collect()
+        let path = PathBuf::from(value);
+        if !path.exists() {
+            return Err(anyhow!(
+                "{} points to a missing config file: {}",
+                CONFIG_ENV_VAR,
+                path.display()
+            ));
+        }
+        Ok(Some(path))
+    }
+
+    /// Locations consulted, in order, when neither a config path nor
+    /// `CLINOTE_CONFIG` is given:
+    /// `./clinote.toml`, then `$XDG_CONFIG_HOME/clinote/config.toml`, then
+    /// `~/.config/clinote/config.toml`.
+    pub fn search_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from("clinote.toml")];
+        if let Some(dirs) = BaseDirs::new() {
+            paths.push(dirs.config_dir().join("clinote").join("config.toml"));
+            let home = dirs
+                .home_dir()
+                .join(".config")
+                .join("clinote")
+                .join("config.toml");
+            if !paths.contains(&home) {
+                paths.push(home);
+            }
+        }
+        paths
+    }
+
+    /// The `[[templates]]` entry called `name`, compared case-insensitively.
+    pub fn custom_template(&self, name: &str) -> Option<&CustomTemplate> {
+        self.templates
+            .iter()
+            .find(|template| template.name.trim().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn format_spec(&self, format: NoteFormat) -> &FormatSpec {
+        match format {
+            NoteFormat::Soap => &self.formats.soap,
+            NoteFormat::Hp => &self.formats.hp,
+            NoteFormat::Discharge => &self.formats.discharge,
+            NoteFormat::Progress => &self.formats.progress,
+            NoteFormat::Operative => &self.formats.operative,
+        }
+    }
+
+    pub fn section_order(&self, format: NoteFormat) -> Vec<String> {
+        self.format_spec(format).section_order.clone()
+    }
+
+    /// Heading comparison key under the configured `[parser] heading_matching`.
+    pub fn heading_key(&self, raw: &str) -> String {
+        util::normalize_heading_key_with(raw, self.parser.heading_matching)
+    }
+
+    /// Resolves a heading against every configured section name, which lets
+    /// custom (non built-in) sections be detected as headings.
+    pub fn resolve_section_name(&self, raw: &str) -> Option<String> {
+        let raw_key = self.heading_key(raw);
+        [
+            &self.formats.soap.section_order,
+            &self.formats.hp.section_order,
+            &self.formats.discharge.section_order,
+            &self.formats.progress.section_order,
+            &self.formats.operative.section_order,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|name| self.heading_key(name) == raw_key)
+        .cloned()
     }
 
     pub fn resolve_heading_alias(&self, raw: &str) -> Option<String> {
-        let raw_key = util::normalize_heading_key(raw);
-        self.heading_aliases.iter().find_map(|(k, v)| {
-            if util::normalize_heading_key(k) == raw_key {
-                Some(v.clone())
-            } else {
-                None
+        self.resolve_heading_alias_entry(raw)
+            .map(|(_, target)| target)
+    }
+
+    /// Like `resolve_heading_alias`, but also returns the alias key that matched.
+    pub fn resolve_heading_alias_entry(&self, raw: &str) -> Option<(String, String)> {
+        self.resolve_heading_alias_entry_for(raw, None)
+    }
+
+    /// Like `resolve_heading_alias_entry`, but checks the aliases of `format`
+    /// first, falling back to the global `heading_aliases`. When several keys
+    /// match (e.g. `Dx` and `DX:`), a key equal to `raw` wins, then the
+    /// smallest key.
+    pub fn resolve_heading_alias_entry_for(
+        &self,
+        raw: &str,
+        format: Option<NoteFormat>,
+    ) -> Option<(String, String)> {
+        let raw_key = self.heading_key(raw);
+        let find = |aliases: &HashMap<String, String>| {
+            if let Some((k, v)) = aliases.get_key_value(raw) {
+                return Some((k.clone(), v.clone()));
             }
-        })
+            aliases
+                .iter()
+                .filter(|(k, _)| self.heading_key(k) == raw_key)
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(k, v)| (k.clone(), v.clone()))
+        };
+        format
+            .and_then(|format| find(&self.format_spec(format).heading_aliases))
+            .or_else(|| find(&self.heading_aliases))
     }
 
     pub fn summary(&self) -> String {
@@ -173,6 +834,14 @@ impl Config {
             "Discharge: {}\n",
             self.section_order(NoteFormat::Discharge).join(", ")
         ));
+        out.push_str(&format!(
+            "Progress: {}\n",
+            self.section_order(NoteFormat::Progress).join(", ")
+        ));
+        out.push_str(&format!(
+            "Operative: {}\n",
+            self.section_order(NoteFormat::Operative).join(", ")
+        ));
         out.push_str("\nHeading aliases:\n");
         if self.heading_aliases.is_empty() {
             out.push_str("(none)\n");
@@ -181,6 +850,17 @@ impl Config {
                 out.push_str(&format!("{} => {}\n", k, v));
             }
         }
+        for format in [
+            NoteFormat::Soap,
+            NoteFormat::Hp,
+            NoteFormat::Discharge,
+            NoteFormat::Progress,
+            NoteFormat::Operative,
+        ] {
+            for (k, v) in &self.format_spec(format).heading_aliases {
+                out.push_str(&format!("[{}] {} => {}\n", format.as_str(), k, v));
+            }
+        }
         out.push_str("\nBundle delimiters:\n");
         for delimiter in &self.bundle.delimiters {
             out.push_str(&format!("- {}\n", delimiter));
@@ -0,0 +1,253 @@
+use crate::models::{NoteFormat, StructuredNote};
+use crate::render;
+use crate::util;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoteRef {
+    pub file: String,
+    pub note_index: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FormatChange {
+    pub before: NoteFormat,
+    pub after: NoteFormat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SectionChange {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoteDiff {
+    pub file: String,
+    pub note_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<FormatChange>,
+    pub added_sections: Vec<String>,
+    pub removed_sections: Vec<String>,
+    pub changed_sections: Vec<SectionChange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiffReport {
+    /// Notes present on both sides.
+    pub notes_compared: usize,
+    pub added_notes: Vec<NoteRef>,
+    pub removed_notes: Vec<NoteRef>,
+    /// Notes on both sides that differ; identical notes are left out.
+    pub changed_notes: Vec<NoteDiff>,
+}
+
+impl DiffReport {
+    pub fn is_empty(&self) -> bool {
+        self.added_notes.is_empty()
+            && self.removed_notes.is_empty()
+            && self.changed_notes.is_empty()
+    }
+}
+
+/// Compares two JSON outputs. Two files are compared directly; two
+/// directories pair their `.json` files by name.
+pub fn diff_paths(before: &Path, after: &Path) -> Result<DiffReport> {
+    let mut report = DiffReport::default();
+    match (before.is_dir(), after.is_dir()) {
+        (false, false) => {
+            let label = util::file_stem(after);
+            diff_into(&mut report, &label, &load_file(before)?, &load_file(after)?);
+        }
+        (true, true) => {
+            let before_files = note_files(before)?;
+            let after_files = note_files(after)?;
+            let names: BTreeSet<&String> = before_files.keys().chain(after_files.keys()).collect();
+            let empty = Vec::new();
+            for name in names {
+                let left = before_files.get(name).unwrap_or(&empty);
+                let right = after_files.get(name).unwrap_or(&empty);
+                diff_into(&mut report, name, left, right);
+            }
+        }
+        _ => {
+            return Err(anyhow!(
+                "Compare two files or two directories, not one of each"
+            ))
+        }
+    }
+    Ok(report)
+}
+
+/// Aligns notes by `note_index` and sections by canonical name; repeated
+/// sections are paired in document order.
+pub fn diff_notes(file: &str, before: &[StructuredNote], after: &[StructuredNote]) -> DiffReport {
+    let mut report = DiffReport::default();
+    diff_into(&mut report, file, before, after);
+    report
+}
+
+fn diff_into<'a>(
+    report: &mut DiffReport,
+    file: &str,
+    before: &'a [StructuredNote],
+    after: &'a [StructuredNote],
+) {
+    let by_index = |notes: &'a [StructuredNote]| {
+        let mut map: BTreeMap<usize, Vec<&'a StructuredNote>> = BTreeMap::new();
+        for note in notes {
+            map.entry(note.note_index).or_default().push(note);
+        }
+        map
+    };
+    let before = by_index(before);
+    let after = by_index(after);
+    let indexes: BTreeSet<usize> = before.keys().chain(after.keys()).copied().collect();
+    let empty = Vec::new();
+    for note_index in indexes {
+        let left = before.get(&note_index).unwrap_or(&empty);
+        let right = after.get(&note_index).unwrap_or(&empty);
+        // A combined `notes.json` repeats indexes across files; pair those in order.
+        for idx in 0..left.len().max(right.len()) {
+            let note_ref = NoteRef {
+                file: file.to_string(),
+                note_index,
+            };
+            match (left.get(idx), right.get(idx)) {
+                (Some(left), Some(right)) => {
+                    report.notes_compared += 1;
+                    let diff = diff_note(file, left, right);
+                    let changed = diff.format.is_some()
+                        || !diff.added_sections.is_empty()
+                        || !diff.removed_sections.is_empty()
+                        || !diff.changed_sections.is_empty();
+                    if changed {
+                        report.changed_notes.push(diff);
+                    }
+                }
+                (Some(_), None) => report.removed_notes.push(note_ref),
+                (None, Some(_)) => report.added_notes.push(note_ref),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+fn diff_note(file: &str, before: &StructuredNote, after: &StructuredNote) -> NoteDiff {
+    let mut diff = NoteDiff {
+        file: file.to_string(),
+        note_index: before.note_index,
+        format: (before.format != after.format).then_some(FormatChange {
+            before: before.format,
+            after: after.format,
+        }),
+        added_sections: Vec::new(),
+        removed_sections: Vec::new(),
+        changed_sections: Vec::new(),
+    };
+    let group = |note: &StructuredNote| {
+        let mut groups: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+        for section in &note.sections {
+            groups
+                .entry(util::normalize_heading_key(section.canonical()))
+                .or_default()
+                .push((section.name.clone(), section.content.clone()));
+        }
+        groups
+    };
+    let left = group(before);
+    let right = group(after);
+    let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    for key in keys {
+        let empty = Vec::new();
+        let left = left.get(key).unwrap_or(&empty);
+        let right = right.get(key).unwrap_or(&empty);
+        for idx in 0..left.len().max(right.len()) {
+            match (left.get(idx), right.get(idx)) {
+                (Some((name, old)), Some((_, new))) if old != new => {
+                    diff.changed_sections.push(SectionChange {
+                        name: name.clone(),
+                        before: old.clone(),
+                        after: new.clone(),
+                    })
+                }
+                (Some((name, _)), None) => diff.removed_sections.push(name.clone()),
+                (None, Some((name, _))) => diff.added_sections.push(name.clone()),
+                _ => {}
+            }
+        }
+    }
+    diff
+}
+
+fn load_file(path: &Path) -> Result<Vec<StructuredNote>> {
+    let content = util::read_to_string(path)?;
+    render::json::parse_notes(&content)
+        .map_err(|err| anyhow!("{} is not structured note JSON: {}", path.display(), err))
+}
+
+/// Notes from the `.json` files directly under `dir`, keyed by file name.
+/// Files that aren't note output (such as `batch_report.json`) are skipped.
+fn note_files(dir: &Path) -> Result<BTreeMap<String, Vec<StructuredNote>>> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let (Some(name), Ok(notes)) = (path.file_name().and_then(|n| n.to_str()), load_file(&path))
+        else {
+            continue;
+        };
+        files.insert(name.to_string(), notes);
+    }
+    Ok(files)
+}
+
+pub fn format_summary(report: &DiffReport) -> String {
+    let mut out = format!(
+        "Compared {} notes: {} changed, {} added, {} removed\n",
+        report.notes_compared,
+        report.changed_notes.len(),
+        report.added_notes.len(),
+        report.removed_notes.len()
+    );
+    if report.is_empty() {
+        out.push_str("No differences\n");
+    }
+    for note in &report.removed_notes {
+        out.push_str(&format!("- {} note {}\n", note.file, note.note_index));
+    }
+    for note in &report.added_notes {
+        out.push_str(&format!("+ {} note {}\n", note.file, note.note_index));
+    }
+    for note in &report.changed_notes {
+        out.push_str(&format!("~ {} note {}\n", note.file, note.note_index));
+        if let Some(change) = &note.format {
+            out.push_str(&format!(
+                "    format: {} -> {}\n",
+                change.before.as_str(),
+                change.after.as_str()
+            ));
+        }
+        for name in &note.removed_sections {
+            out.push_str(&format!("    - {}\n", name));
+        }
+        for name in &note.added_sections {
+            out.push_str(&format!("    + {}\n", name));
+        }
+        for change in &note.changed_sections {
+            out.push_str(&format!(
+                "    ~ {} ({} -> {} chars)\n",
+                change.name,
+                change.before.chars().count(),
+                change.after.chars().count()
+            ));
+        }
+    }
+    out
+}
@@ -0,0 +1,94 @@
+use glob::Pattern;
+use std::path::{Component, Path, PathBuf};
+
+pub const IGNORE_FILE: &str = ".clinoteignore";
+
+/// Patterns from a `.clinoteignore` file, matched relative to the directory
+/// that holds it. Follows the common `.gitignore` shapes: `#` comments, a
+/// trailing `/` to match a directory and everything under it, and patterns
+/// without a `/` matching a file or directory name at any depth.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreList {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreList {
+    /// Loads `<dir>/.clinoteignore`; a missing or unreadable file ignores nothing.
+    pub fn load(dir: &Path) -> Self {
+        let content = std::fs::read_to_string(dir.join(IGNORE_FILE)).unwrap_or_default();
+        Self::parse(dir, &content)
+    }
+
+    pub fn parse(root: &Path, content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let dir_only = line.ends_with('/');
+                let body = line.trim_end_matches('/');
+                let anchored = body.contains('/');
+                Pattern::new(body.trim_start_matches('/'))
+                    .ok()
+                    .map(|pattern| Rule {
+                        pattern,
+                        dir_only,
+                        anchored,
+                    })
+            })
+            .collect();
+        Self {
+            root: root.to_path_buf(),
+            rules,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        if parts.is_empty() {
+            return false;
+        }
+
+        self.rules.iter().any(|rule| {
+            // Directory rules only apply to ancestors, never to the file itself.
+            let candidates = if rule.dir_only {
+                parts.len() - 1
+            } else {
+                parts.len()
+            };
+            (0..candidates).any(|idx| {
+                if rule.anchored {
+                    rule.pattern.matches(&parts[..=idx].join("/"))
+                } else {
+                    rule.pattern.matches(&parts[idx])
+                }
+            })
+        })
+    }
+
+    pub fn retain(&self, paths: &mut Vec<PathBuf>) {
+        paths.retain(|path| !self.is_ignored(path));
+    }
+}
@@ -1,11 +1,21 @@
 pub mod cli;
 pub mod config;
+pub mod diff;
+pub mod ignore;
 pub mod interactive;
+pub mod logging;
 pub mod models;
 pub mod parser;
 pub mod render;
 pub mod reports;
 pub mod samples;
+pub mod stats;
 pub mod util;
 pub mod validate;
+pub mod verify;
+pub mod wordfreq;
 pub mod selftest;
+
+pub use parser::headings::{resolve_heading, resolve_heading_for};
+pub use parser::list_sections;
+pub use validate::validate_document;
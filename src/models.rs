@@ -1,13 +1,28 @@
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 #[clap(rename_all = "lowercase")]
 pub enum NoteFormat {
     Soap,
     Hp,
     Discharge,
+    Progress,
+    Operative,
+}
+
+impl NoteFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteFormat::Soap => "soap",
+            NoteFormat::Hp => "hp",
+            NoteFormat::Discharge => "discharge",
+            NoteFormat::Progress => "progress",
+            NoteFormat::Operative => "operative",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum, PartialEq, Eq)]
@@ -26,6 +41,35 @@ pub enum CsvLayout {
     Long,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    KeepAll,
+    KeepHighest,
+    Merge,
+}
+
+/// How heading text is folded before it is compared with known headings.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadingMatching {
+    /// ASCII letters and digits only, uppercased; other characters are dropped.
+    Ascii,
+    /// Unicode-aware case folding, keeping accented letters.
+    Unicode,
+    /// Unicode case folding with diacritics stripped, so `Évaluation` matches `Evaluation`.
+    UnicodeNoAccents,
+}
+
+/// Severity of a validation issue.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum WarningSeverity {
@@ -34,6 +78,46 @@ pub enum WarningSeverity {
     Error,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadingMethod {
+    AllCaps,
+    Colon,
+    Inline,
+    Fallback,
+    /// Markdown `#`-prefixed heading such as `## Assessment`.
+    Atx,
+    /// Heading text underlined by a line of `=` or `-` on the next line.
+    Setext,
+    #[serde(rename = "none")]
+    NoHeading,
+}
+
+impl HeadingMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HeadingMethod::AllCaps => "allcaps",
+            HeadingMethod::Colon => "colon",
+            HeadingMethod::Inline => "inline",
+            HeadingMethod::Fallback => "fallback",
+            HeadingMethod::Atx => "atx",
+            HeadingMethod::Setext => "setext",
+            HeadingMethod::NoHeading => "none",
+        }
+    }
+}
+
+/// How a heading was matched to its canonical name: through a configured
+/// alias (with the alias key that matched), the built-in heading map, or a
+/// configured `section_order` name.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadingSource {
+    Alias(String),
+    Builtin,
+    Config,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SectionName {
     #[serde(rename = "Subjective", alias = "S", alias = "SUBJECTIVE")]
@@ -122,6 +206,46 @@ pub enum SectionName {
         alias = "INSTRUCTIONS"
     )]
     Instructions,
+    #[serde(
+        rename = "Interval History",
+        alias = "Interval Hx",
+        alias = "INTERVAL HISTORY"
+    )]
+    IntervalHistory,
+    #[serde(rename = "Events", alias = "Overnight Events", alias = "EVENTS")]
+    Events,
+    #[serde(
+        rename = "Preoperative Diagnosis",
+        alias = "Preop Dx",
+        alias = "Pre-op Diagnosis",
+        alias = "PREOPERATIVE DIAGNOSIS"
+    )]
+    PreopDx,
+    #[serde(
+        rename = "Postoperative Diagnosis",
+        alias = "Postop Dx",
+        alias = "Post-op Diagnosis",
+        alias = "POSTOPERATIVE DIAGNOSIS"
+    )]
+    PostopDx,
+    #[serde(
+        rename = "Procedure",
+        alias = "Procedure Performed",
+        alias = "Operation",
+        alias = "PROCEDURE"
+    )]
+    Procedure,
+    #[serde(rename = "Findings", alias = "Operative Findings", alias = "FINDINGS")]
+    Findings,
+    #[serde(
+        rename = "Estimated Blood Loss",
+        alias = "EBL",
+        alias = "Blood Loss",
+        alias = "ESTIMATED BLOOD LOSS"
+    )]
+    Ebl,
+    #[serde(rename = "Specimens", alias = "Specimen", alias = "SPECIMENS")]
+    Specimens,
     #[serde(rename = "Narrative", alias = "Other")]
     Narrative,
 }
@@ -146,16 +270,64 @@ impl SectionName {
             SectionName::FollowUp => "Follow-up",
             SectionName::Disposition => "Disposition",
             SectionName::Instructions => "Instructions",
+            SectionName::IntervalHistory => "Interval History",
+            SectionName::Events => "Events",
+            SectionName::PreopDx => "Preoperative Diagnosis",
+            SectionName::PostopDx => "Postoperative Diagnosis",
+            SectionName::Procedure => "Procedure",
+            SectionName::Findings => "Findings",
+            SectionName::Ebl => "Estimated Blood Loss",
+            SectionName::Specimens => "Specimens",
             SectionName::Narrative => "Narrative",
         }
     }
 }
 
+/// A 1-based, inclusive range of source lines. Lines count from the start of
+/// the note, so inside a bundle they are relative to that note, not the file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Span {
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Section {
     pub name: String,
     pub content: String,
     pub confidence: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_by: Option<HeadingMethod>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<HeadingSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<usize>,
+    /// Canonical section name when `name` holds the heading as written
+    /// (`parser.preserve_heading_case`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_name: Option<String>,
+    /// The section's source lines before normalization, heading line included
+    /// (`normalize.keep_raw_content`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_content: Option<String>,
+    /// The heading as the author wrote it, when it differs from `name`
+    /// (e.g. "Hx" mapped to PMH).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_heading: Option<String>,
+    /// Source lines the section was read from, heading line included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// Vital signs found in an Objective section (`parser.parse_vitals`),
+    /// keyed `bp`, `hr`, `rr`, `temp` and `spo2`; `content` is left as is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vitals: Option<BTreeMap<String, String>>,
+}
+
+impl Section {
+    /// The canonical name, used for ordering and validation matching.
+    pub fn canonical(&self) -> &str {
+        self.canonical_name.as_deref().unwrap_or(&self.name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -165,8 +337,14 @@ pub struct ParseWarning {
     pub line_start: usize,
     pub line_end: usize,
     pub severity: WarningSeverity,
+    /// The heading as written, for `unmapped_heading` warnings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
 }
 
+/// Version of the JSON envelope layout written with `json.wrap_envelope`.
+pub const SCHEMA_VERSION: &str = "1";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metadata {
     pub generated_at: String,
@@ -181,15 +359,46 @@ pub struct StructuredNote {
     pub note_index: usize,
     pub sections: Vec<Section>,
     pub warnings: Vec<ParseWarning>,
+    #[serde(default)]
+    pub content_hash: String,
+    #[serde(flatten)]
+    pub confidence: ConfidenceSummary,
     pub metadata: Metadata,
 }
 
+/// Per-note spread of section confidences; all `None` when there are no sections.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConfidenceSummary {
+    #[serde(default)]
+    pub min_confidence: Option<f32>,
+    #[serde(default)]
+    pub mean_confidence: Option<f32>,
+    #[serde(default)]
+    pub max_confidence: Option<f32>,
+}
+
+impl ConfidenceSummary {
+    pub fn from_sections(sections: &[Section]) -> Self {
+        if sections.is_empty() {
+            return Self::default();
+        }
+        let values = sections.iter().map(|s| s.confidence);
+        Self {
+            min_confidence: values.clone().reduce(f32::min),
+            mean_confidence: Some(values.clone().sum::<f32>() / sections.len() as f32),
+            max_confidence: values.reduce(f32::max),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeadingLine {
     pub line_num: usize,
     pub raw: String,
     pub heading: String,
     pub inline_content: Option<String>,
+    pub method: HeadingMethod,
+    pub source: HeadingSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -200,4 +409,11 @@ pub struct SectionCandidate {
     pub start_line: usize,
     pub end_line: usize,
     pub confidence: f32,
+    pub detected_by: HeadingMethod,
+    pub resolved_by: Option<HeadingSource>,
+    pub part: Option<usize>,
+    /// Heading text as written, set when `parser.preserve_heading_case` is on.
+    pub display_name: Option<String>,
+    pub raw_content: Option<String>,
+    pub vitals: Option<BTreeMap<String, String>>,
 }
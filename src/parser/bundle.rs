@@ -1,11 +1,13 @@
 use crate::config::Config;
 use crate::models::{BundleMode, ParseWarning, WarningSeverity};
-use crate::parser::warnings;
-use once_cell::sync::Lazy;
+use crate::parser::{headings, warnings};
+use crate::util;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-static DATE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^\s*(\d{4}-\d{2}-\d{2}|\d{2}/\d{2}/\d{4})").unwrap());
+/// How many leading non-blank lines of each note are considered banner candidates.
+const BANNER_MAX_LINES: usize = 5;
 
 pub fn split_bundle(
     text: &str,
@@ -19,15 +21,35 @@ pub fn split_bundle(
     }
 }
 
+/// Joins notes into one bundle, one `delimiter` line between each pair, so
+/// `split_bundle` with that delimiter configured gives them back in order.
+pub fn merge_bundle<S: AsRef<str>>(notes: &[S], delimiter: &str) -> String {
+    let separator = format!("\n{}\n", delimiter.trim());
+    let mut merged = notes
+        .iter()
+        .map(|note| note.as_ref().trim_matches('\n'))
+        .collect::<Vec<_>>()
+        .join(&separator);
+    merged.push('\n');
+    merged
+}
+
 fn split_bundle_internal(
     text: &str,
     config: &Config,
     strict: bool,
 ) -> (Vec<String>, Vec<ParseWarning>) {
     let mut warnings_list = Vec::new();
-    let mut notes = split_on_delimiters(text, &config.bundle.delimiters);
+    let date_patterns = util::compiled_patterns(&config.bundle.date_patterns);
+    let spans = line_spans(text);
+    let lines: Vec<&str> = spans.iter().map(|span| &text[span.clone()]).collect();
+    let mut notes = split_on_delimiters(
+        &lines,
+        &config.bundle.delimiters,
+        config.bundle.case_sensitive_delimiters,
+    );
     if notes.len() <= 1 {
-        notes = split_on_dates(text);
+        notes = split_on_dates(&lines, &date_patterns, config.bundle.min_dates_to_split);
     }
 
     if notes.len() <= 1 {
@@ -43,51 +65,205 @@ fn split_bundle_internal(
         return (vec![text.to_string()], warnings_list);
     }
 
+    if config.bundle.strip_banners {
+        warnings_list.extend(strip_banners(&mut notes, &lines, &date_patterns, config));
+    }
+
+    // Slice the input itself so line endings and indentation stay verbatim
+    // for `normalize.keep_raw_content`.
+    let notes = notes
+        .iter()
+        .map(|note| {
+            if note.is_empty() {
+                String::new()
+            } else {
+                text[spans[note.start].start..spans[note.end - 1].end].to_string()
+            }
+        })
+        .collect();
     (notes, warnings_list)
 }
 
-fn split_on_delimiters(text: &str, delimiters: &[String]) -> Vec<String> {
-    let mut notes = Vec::new();
-    let mut current = Vec::new();
-    for line in text.lines() {
-        let trimmed = line.trim();
-        if delimiters.iter().any(|d| d.trim() == trimmed) {
-            if !current.is_empty() {
-                notes.push(current.join("\n").trim().to_string());
-                current.clear();
+/// Byte ranges of the input's lines, terminators excluded; `\n`, `\r\n` and
+/// a lone `\r` all end a line, as in `normalize::raw_lines`.
+fn line_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\n' => {
+                spans.push(start..idx);
+                start = idx + 1;
             }
-            continue;
+            b'\r' => {
+                spans.push(start..idx);
+                if bytes.get(idx + 1) == Some(&b'\n') {
+                    idx += 1;
+                }
+                start = idx + 1;
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+    if start < text.len() {
+        spans.push(start..text.len());
+    }
+    spans
+}
+
+/// `range` without its leading and trailing blank lines.
+fn trim_blank_lines(lines: &[&str], range: Range<usize>) -> Range<usize> {
+    let mut range = range;
+    while range.start < range.end && lines[range.start].trim().is_empty() {
+        range.start += 1;
+    }
+    while range.end > range.start && lines[range.end - 1].trim().is_empty() {
+        range.end -= 1;
+    }
+    range
+}
+
+/// Removes leading lines shared by at least `banner_threshold` of the notes,
+/// such as a repeated institutional banner. Headings and date lines are never
+/// treated as banner text, and stripping stops at the first line that isn't one.
+/// Each stripped note gets a warning with the banner's lines in the input.
+fn strip_banners(
+    notes: &mut [Range<usize>],
+    lines: &[&str],
+    date_patterns: &[Regex],
+    config: &Config,
+) -> Vec<ParseWarning> {
+    let threshold = config.bundle.banner_threshold.clamp(0.0, 1.0);
+    let required = ((notes.len() as f32 * threshold).ceil() as usize).max(2);
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for note in notes.iter() {
+        let leading: HashSet<&str> = leading_lines(&lines[note.clone()]).collect();
+        for line in leading {
+            *counts.entry(line).or_insert(0) += 1;
+        }
+    }
+    let banner: HashSet<&str> = counts
+        .iter()
+        .filter(|(line, count)| {
+            **count >= required
+                && !is_date_line(line, date_patterns)
+                && headings::detect_heading(line, config).is_none()
+        })
+        .map(|(line, _)| *line)
+        .collect();
+    if banner.is_empty() {
+        return Vec::new();
+    }
+
+    let total = notes.len();
+    let mut warnings_list = Vec::new();
+    for (note_idx, note) in notes.iter_mut().enumerate() {
+        let mut stripped: Vec<usize> = Vec::new();
+        for idx in note.clone() {
+            let trimmed = lines[idx].trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if !banner.contains(trimmed) {
+                break;
+            }
+            stripped.push(idx);
         }
-        current.push(line.to_string());
+        let (Some(&first), Some(&last)) = (stripped.first(), stripped.last()) else {
+            continue;
+        };
+        *note = trim_blank_lines(lines, last + 1..note.end);
+        let text: Vec<&str> = stripped.iter().map(|&idx| lines[idx].trim()).collect();
+        warnings_list.push(warnings::warning(
+            "stripped_banner",
+            format!(
+                "Stripped banner from note {} of {}: {}",
+                note_idx + 1,
+                total,
+                text.join(" | ")
+            ),
+            first + 1,
+            last + 1,
+            WarningSeverity::Info,
+        ));
     }
-    if !current.is_empty() {
-        notes.push(current.join("\n").trim().to_string());
+    warnings_list
+}
+
+fn leading_lines<'a>(note: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    note.iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .take(BANNER_MAX_LINES)
+}
+
+/// Line ranges of the notes between delimiter lines, blank lines trimmed.
+fn split_on_delimiters(
+    lines: &[&str],
+    delimiters: &[String],
+    case_sensitive: bool,
+) -> Vec<Range<usize>> {
+    let delimiters: Vec<String> = delimiters
+        .iter()
+        .map(|d| delimiter_key(d, case_sensitive))
+        .collect();
+    let mut notes = Vec::new();
+    let mut start = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        if delimiters.contains(&delimiter_key(line, case_sensitive)) {
+            if idx > start {
+                notes.push(trim_blank_lines(lines, start..idx));
+            }
+            start = idx + 1;
+        }
     }
-    if notes.is_empty() {
-        notes.push(text.to_string());
+    if lines.len() > start {
+        notes.push(trim_blank_lines(lines, start..lines.len()));
     }
     notes
 }
 
-fn split_on_dates(text: &str) -> Vec<String> {
+/// Case-sensitive keys are only trimmed; otherwise whitespace runs collapse to
+/// one space and the line is lowercased.
+fn delimiter_key(line: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        line.trim().to_string()
+    } else {
+        line.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+}
+
+fn is_date_line(line: &str, date_patterns: &[Regex]) -> bool {
+    date_patterns.iter().any(|re| re.is_match(line))
+}
+
+/// Line ranges of notes that each start at a date line; none when fewer than
+/// `min_dates` date lines are found.
+fn split_on_dates(lines: &[&str], date_patterns: &[Regex], min_dates: usize) -> Vec<Range<usize>> {
     let mut notes = Vec::new();
-    let mut current = Vec::new();
+    let mut start = 0;
     let mut found = 0;
-    for line in text.lines() {
-        if DATE_RE.is_match(line) {
-            if !current.is_empty() {
-                notes.push(current.join("\n").trim().to_string());
-                current.clear();
+    for (idx, line) in lines.iter().enumerate() {
+        if is_date_line(line, date_patterns) {
+            if idx > start {
+                notes.push(trim_blank_lines(lines, start..idx));
             }
+            start = idx;
             found += 1;
         }
-        current.push(line.to_string());
     }
-    if !current.is_empty() {
-        notes.push(current.join("\n").trim().to_string());
+    if lines.len() > start {
+        notes.push(trim_blank_lines(lines, start..lines.len()));
     }
-    if found <= 1 {
-        vec![text.to_string()]
+    if found < min_dates.max(1) {
+        Vec::new()
     } else {
         notes
     }
@@ -0,0 +1,94 @@
+use crate::config::Config;
+use crate::models::{HeadingMethod, NoteFormat};
+use crate::parser::{extract_candidates, normalize, ParseOptions};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineKind {
+    Heading,
+    Inline,
+    Body,
+    Blank,
+    Unassigned,
+}
+
+impl LineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineKind::Heading => "heading",
+            LineKind::Inline => "inline",
+            LineKind::Body => "body",
+            LineKind::Blank => "blank",
+            LineKind::Unassigned => "unassigned",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineTrace {
+    pub line: usize,
+    pub text: String,
+    pub kind: LineKind,
+    pub method: Option<HeadingMethod>,
+    pub section: Option<String>,
+}
+
+/// Re-runs extraction and classifies every normalized line. Only used by
+/// `parse --explain`, so the normal parse path pays nothing for it.
+pub fn explain_note(
+    text: &str,
+    format: NoteFormat,
+    config: &Config,
+    options: ParseOptions,
+) -> Vec<LineTrace> {
+    let normalized = normalize::normalize_text_with(text, config.normalize.preserve_bullets);
+    let (candidates, _) = extract_candidates(text, format, config, options);
+
+    normalized
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let line_num = idx + 1;
+            let owner = candidates
+                .iter()
+                .find(|c| c.start_line <= line_num && line_num <= c.end_line);
+            let headed = owner
+                .filter(|c| c.detected_by != HeadingMethod::NoHeading && c.start_line == line_num);
+            let kind = match (headed, owner) {
+                (Some(c), _) => match c.detected_by {
+                    HeadingMethod::Inline | HeadingMethod::Fallback => LineKind::Inline,
+                    _ => LineKind::Heading,
+                },
+                _ if line.trim().is_empty() => LineKind::Blank,
+                (None, Some(_)) => LineKind::Body,
+                (None, None) => LineKind::Unassigned,
+            };
+            LineTrace {
+                line: line_num,
+                text: line.to_string(),
+                kind,
+                method: headed.map(|c| c.detected_by),
+                section: owner.map(|c| c.name.clone()),
+            }
+        })
+        .collect()
+}
+
+pub fn format_trace(trace: &[LineTrace]) -> String {
+    let mut out = String::new();
+    for entry in trace {
+        let kind = match entry.method {
+            Some(method) => format!("{} ({})", entry.kind.as_str(), method.as_str()),
+            None => entry.kind.as_str().to_string(),
+        };
+        out.push_str(&format!(
+            "{:>4} | {:<18} | {:<16} | {}\n",
+            entry.line,
+            kind,
+            entry.section.as_deref().unwrap_or("-"),
+            entry.text
+        ));
+    }
+    out
+}
@@ -1,15 +1,50 @@
 use crate::config::Config;
-use crate::models::HeadingLine;
+use crate::models::{HeadingLine, HeadingMatching, HeadingMethod, HeadingSource, NoteFormat};
 use crate::util;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
 use std::collections::HashMap;
 
-static INLINE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(?P<h>[A-Za-z0-9 /&.-]{1,40}):\s*(?P<rest>.+)$").unwrap());
+/// What a piece of heading text resolves to under a config, for
+/// `clinote resolve-heading` and editor tooling.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct HeadingResolution {
+    pub input: String,
+    /// Canonical section name; `None` when the heading is unmapped.
+    pub canonical: Option<String>,
+    /// `alias`, `builtin`, `config` or `none`.
+    pub method: String,
+    /// The configured alias key that matched, for `alias` resolutions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+static INLINE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<h>[A-Za-z0-9 /&.-]{1,40}(?:\([^()]{1,40}\))?):\s*(?P<rest>.+)$").unwrap()
+});
 static COLON_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(?P<h>[A-Za-z0-9 /&.-]{2,40}):\s*$").unwrap());
-static ALL_CAPS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z][A-Z0-9 /&-]{1,40}$").unwrap());
+    Lazy::new(|| Regex::new(r"^(?P<h>[A-Za-z0-9 /&.-]{2,40}(?:\([^()]{1,40}\))?):\s*$").unwrap());
+static ALL_CAPS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Z][A-Z0-9 /&-]{1,40}(?:\([^()]{1,40}\))?$").unwrap());
+
+// Variants that accept non-ASCII letters, used when `heading_matching` is not `ascii`.
+static UNICODE_INLINE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<h>[\p{L}\p{N} /&.-]{1,40}(?:\([^()]{1,40}\))?):\s*(?P<rest>.+)$").unwrap()
+});
+static UNICODE_COLON_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<h>[\p{L}\p{N} /&.-]{2,40}(?:\([^()]{1,40}\))?):\s*$").unwrap());
+static UNICODE_ALL_CAPS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\p{Lu}[\p{Lu}\p{N} /&-]{1,40}(?:\([^()]{1,40}\))?$").unwrap());
+
+/// Markdown ATX heading: one to six `#`, whitespace, text, optional closing `#`s.
+static ATX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^#{1,6}(?:\s+(?P<text>.*?))?(?:\s+#+)?\s*$").unwrap());
+/// Setext underline: a line of at least three `=` or `-`.
+static UNDERLINE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:={3,}|-{3,})$").unwrap());
+/// Dictation ordinal in front of a heading: `1. `, `2) `.
+static ORDINAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+[.)]\s*").unwrap());
+static FIELD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?P<label>[^:]{1,40}):\s*\S").unwrap());
 
 static HEADING_MAP: Lazy<HashMap<String, String>> = Lazy::new(|| {
     let mut map = HashMap::new();
@@ -54,6 +89,34 @@ static HEADING_MAP: Lazy<HashMap<String, String>> = Lazy::new(|| {
         ("DISPO", "Disposition"),
         ("INSTRUCTIONS", "Instructions"),
         ("DISCHARGE INSTRUCTIONS", "Instructions"),
+        ("INTERVAL HISTORY", "Interval History"),
+        ("INTERVAL HX", "Interval History"),
+        ("EVENTS", "Events"),
+        ("OVERNIGHT EVENTS", "Events"),
+        ("PREOPERATIVE DIAGNOSIS", "Preoperative Diagnosis"),
+        ("PREOPERATIVE DX", "Preoperative Diagnosis"),
+        ("PREOP DIAGNOSIS", "Preoperative Diagnosis"),
+        ("PREOP DX", "Preoperative Diagnosis"),
+        ("PRE OP DIAGNOSIS", "Preoperative Diagnosis"),
+        ("PRE OP DX", "Preoperative Diagnosis"),
+        ("POSTOPERATIVE DIAGNOSIS", "Postoperative Diagnosis"),
+        ("POSTOPERATIVE DX", "Postoperative Diagnosis"),
+        ("POSTOP DIAGNOSIS", "Postoperative Diagnosis"),
+        ("POSTOP DX", "Postoperative Diagnosis"),
+        ("POST OP DIAGNOSIS", "Postoperative Diagnosis"),
+        ("POST OP DX", "Postoperative Diagnosis"),
+        ("PROCEDURE", "Procedure"),
+        ("PROCEDURES", "Procedure"),
+        ("PROCEDURE PERFORMED", "Procedure"),
+        ("OPERATION", "Procedure"),
+        ("FINDINGS", "Findings"),
+        ("OPERATIVE FINDINGS", "Findings"),
+        ("ESTIMATED BLOOD LOSS", "Estimated Blood Loss"),
+        ("EBL", "Estimated Blood Loss"),
+        ("BLOOD LOSS", "Estimated Blood Loss"),
+        ("SPECIMENS", "Specimens"),
+        ("SPECIMEN", "Specimens"),
+        ("SPECIMENS REMOVED", "Specimens"),
     ];
     for (k, v) in pairs {
         map.insert(k.to_string(), v.to_string());
@@ -62,55 +125,278 @@ static HEADING_MAP: Lazy<HashMap<String, String>> = Lazy::new(|| {
 });
 
 pub fn scan_headings(lines: &[String], config: &Config) -> Vec<HeadingLine> {
+    scan_headings_for(lines, config, None)
+}
+
+/// Like `scan_headings`, resolving `format`'s own heading aliases first.
+pub fn scan_headings_for(
+    lines: &[String],
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Vec<HeadingLine> {
     let mut headings = Vec::new();
-    for (idx, line) in lines.iter().enumerate() {
-        if let Some((heading, inline)) = detect_heading(line, config) {
+    let skip = demographics_block_len(lines, config);
+    let mut underline = None;
+    for (idx, line) in lines.iter().enumerate().skip(skip) {
+        if underline == Some(idx) {
+            continue;
+        }
+        let underlined = lines
+            .get(idx + 1)
+            .is_some_and(|next| UNDERLINE_RE.is_match(next.trim()));
+        if let Some((heading, source)) = underlined
+            .then(|| detect_setext(line, config, format))
+            .flatten()
+        {
+            underline = Some(idx + 1);
+            headings.push(HeadingLine {
+                line_num: idx + 1,
+                raw: line.clone(),
+                heading,
+                inline_content: None,
+                method: HeadingMethod::Setext,
+                source,
+            });
+            continue;
+        }
+        if let Some((heading, inline, method, source)) = detect(line, config, format) {
             headings.push(HeadingLine {
                 line_num: idx + 1,
                 raw: line.clone(),
                 heading,
                 inline_content: inline,
+                method,
+                source,
             });
         }
     }
     headings
 }
 
+/// Number of leading lines that form a demographics header: blank lines and
+/// `Key: value` lines whose key is one of `parser.demographic_fields`. These
+/// lines are never treated as headings, so `Service: Medicine` stays header
+/// text even when `Service` is a configured section.
+pub fn demographics_block_len<S: AsRef<str>>(lines: &[S], config: &Config) -> usize {
+    let fields: Vec<String> = config
+        .parser
+        .demographic_fields
+        .iter()
+        .map(|field| config.heading_key(field))
+        .collect();
+    if fields.is_empty() {
+        return 0;
+    }
+    let mut end = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.as_ref().trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let is_field = FIELD_RE
+            .captures(trimmed)
+            .map(|caps| fields.contains(&config.heading_key(&caps["label"])))
+            .unwrap_or(false);
+        if !is_field {
+            break;
+        }
+        end = idx + 1;
+    }
+    end
+}
+
+/// `text` without a leading list ordinal such as `1. ` or `2) `.
+pub fn strip_ordinal(text: &str) -> &str {
+    match ORDINAL_RE.find(text) {
+        Some(m) => &text[m.end()..],
+        None => text,
+    }
+}
+
 pub fn detect_heading(line: &str, config: &Config) -> Option<(String, Option<String>)> {
+    detect_heading_with_method(line, config).map(|(heading, inline, _)| (heading, inline))
+}
+
+pub fn detect_heading_with_method(
+    line: &str,
+    config: &Config,
+) -> Option<(String, Option<String>, HeadingMethod)> {
+    detect(line, config, None).map(|(heading, inline, method, _)| (heading, inline, method))
+}
+
+fn detect(
+    line: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Option<(String, Option<String>, HeadingMethod, HeadingSource)> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return None;
     }
+    let (all_caps_re, colon_re, inline_re) = match config.parser.heading_matching {
+        HeadingMatching::Ascii => (&*ALL_CAPS_RE, &*COLON_RE, &*INLINE_RE),
+        _ => (
+            &*UNICODE_ALL_CAPS_RE,
+            &*UNICODE_COLON_RE,
+            &*UNICODE_INLINE_RE,
+        ),
+    };
+
+    if let Some(caps) = ATX_RE.captures(trimmed) {
+        let text = caps.name("text").map(|m| m.as_str().trim()).unwrap_or("");
+        let text = strip_ordinal(text);
+        if text.is_empty() {
+            return None;
+        }
+        if let Some(caps) = inline_re.captures(text) {
+            let raw = caps.name("h").map(|m| m.as_str()).unwrap_or("");
+            let rest = caps.name("rest").map(|m| m.as_str()).unwrap_or("");
+            if let Some((mapped, source)) =
+                canonicalize_heading_with_source_for(raw, config, format)
+            {
+                return Some((
+                    mapped,
+                    Some(rest.trim().to_string()),
+                    HeadingMethod::Inline,
+                    source,
+                ));
+            }
+        }
+        let raw = text.trim_end_matches(':').trim_end();
+        return canonicalize_heading_with_source_for(raw, config, format)
+            .map(|(mapped, source)| (mapped, None, HeadingMethod::Atx, source));
+    }
+
+    let trimmed = strip_ordinal(trimmed);
+    if trimmed.is_empty() {
+        return None;
+    }
 
-    if let Some(caps) = ALL_CAPS_RE.captures(trimmed) {
+    // The all-caps pattern has no `:`, so `PLAN: titrate` falls through to the
+    // inline branch below and keeps its text.
+    if let Some(caps) = all_caps_re.captures(trimmed) {
         let raw = caps.get(0).map(|m| m.as_str()).unwrap_or("");
-        if let Some(mapped) = canonicalize_heading(raw, config) {
-            return Some((mapped, None));
+        if let Some((mapped, source)) = canonicalize_heading_with_source_for(raw, config, format) {
+            return Some((mapped, None, HeadingMethod::AllCaps, source));
         }
     }
 
-    if let Some(caps) = COLON_RE.captures(trimmed) {
+    if let Some(caps) = colon_re.captures(trimmed) {
         let raw = caps.name("h").map(|m| m.as_str()).unwrap_or("");
-        if let Some(mapped) = canonicalize_heading(raw, config) {
-            return Some((mapped, None));
+        if let Some((mapped, source)) = canonicalize_heading_with_source_for(raw, config, format) {
+            return Some((mapped, None, HeadingMethod::Colon, source));
         }
     }
 
-    if let Some(caps) = INLINE_RE.captures(trimmed) {
+    if let Some(caps) = inline_re.captures(trimmed) {
         let raw = caps.name("h").map(|m| m.as_str()).unwrap_or("");
         let rest = caps.name("rest").map(|m| m.as_str()).unwrap_or("");
-        if let Some(mapped) = canonicalize_heading(raw, config) {
-            return Some((mapped, Some(rest.trim().to_string())));
+        if let Some((mapped, source)) = canonicalize_heading_with_source_for(raw, config, format) {
+            return Some((
+                mapped,
+                Some(rest.trim().to_string()),
+                HeadingMethod::Inline,
+                source,
+            ));
         }
     }
 
     None
 }
 
+/// Heading text of a line followed by an underline; the whole line is the
+/// heading, so only a trailing `:` and a dictation ordinal are dropped.
+fn detect_setext(
+    line: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Option<(String, HeadingSource)> {
+    let text = strip_ordinal(line.trim()).trim_end_matches(':').trim_end();
+    if text.is_empty() {
+        return None;
+    }
+    canonicalize_heading_with_source_for(text, config, format)
+}
+
 pub fn canonicalize_heading(raw: &str, config: &Config) -> Option<String> {
-    if let Some(mapped) = config.resolve_heading_alias(raw) {
-        return Some(mapped);
+    canonicalize_heading_for(raw, config, None)
+}
+
+/// Like `canonicalize_heading`, consulting `format`'s heading aliases before
+/// the global ones.
+pub fn canonicalize_heading_for(
+    raw: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Option<String> {
+    canonicalize_heading_with_source_for(raw, config, format).map(|(name, _)| name)
+}
+
+pub fn canonicalize_heading_with_source(
+    raw: &str,
+    config: &Config,
+) -> Option<(String, HeadingSource)> {
+    canonicalize_heading_with_source_for(raw, config, None)
+}
+
+pub fn canonicalize_heading_with_source_for(
+    raw: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Option<(String, HeadingSource)> {
+    lookup_heading(raw, config, format).or_else(|| {
+        let stripped = util::strip_trailing_parenthetical(raw);
+        if stripped.len() < raw.trim().len() {
+            lookup_heading(stripped, config, format)
+        } else {
+            None
+        }
+    })
+}
+
+/// Resolves heading text (with or without a trailing `:`) the way the parser
+/// would, without parsing a note.
+pub fn resolve_heading(raw: &str, config: &Config) -> HeadingResolution {
+    resolve_heading_for(raw, config, None)
+}
+
+/// Like `resolve_heading`, consulting `format`'s heading aliases before the
+/// global ones.
+pub fn resolve_heading_for(
+    raw: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> HeadingResolution {
+    let text = raw.trim();
+    let text = text.strip_suffix(':').unwrap_or(text).trim();
+    let resolved = canonicalize_heading_with_source_for(text, config, format);
+    let (method, alias) = match resolved.as_ref().map(|(_, source)| source) {
+        Some(HeadingSource::Alias(key)) => ("alias", Some(key.clone())),
+        Some(HeadingSource::Builtin) => ("builtin", None),
+        Some(HeadingSource::Config) => ("config", None),
+        None => ("none", None),
+    };
+    HeadingResolution {
+        input: raw.to_string(),
+        canonical: resolved.map(|(name, _)| name),
+        method: method.to_string(),
+        alias,
+    }
+}
+
+fn lookup_heading(
+    raw: &str,
+    config: &Config,
+    format: Option<NoteFormat>,
+) -> Option<(String, HeadingSource)> {
+    if let Some((alias, mapped)) = config.resolve_heading_alias_entry_for(raw, format) {
+        return Some((mapped, HeadingSource::Alias(alias)));
+    }
+    let key = config.heading_key(raw);
+    if let Some(mapped) = HEADING_MAP.get(&key) {
+        return Some((mapped.clone(), HeadingSource::Builtin));
     }
-    let key = util::normalize_heading_key(raw);
-    HEADING_MAP.get(&key).cloned()
+    config
+        .resolve_section_name(raw)
+        .map(|name| (name, HeadingSource::Config))
 }
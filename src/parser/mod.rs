@@ -1,17 +1,26 @@
 pub mod bundle;
+pub mod explain;
 pub mod headings;
 pub mod normalize;
 pub mod sectionize;
+pub mod vitals;
 pub mod warnings;
 
 use crate::config::Config;
-use crate::models::{BundleMode, NoteFormat, ParseWarning, SectionCandidate, StructuredNote};
+use crate::models::{
+    BundleMode, NoteFormat, ParseWarning, SectionCandidate, SectionName, StructuredNote,
+};
 use crate::util;
+use crate::validate::{Template, TemplateRules};
 use anyhow::Result;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ParseOptions {
     pub apply_heuristics: bool,
+    /// Candidates below this confidence are dropped with a
+    /// `low_confidence_dropped` warning; 0.0 keeps everything.
+    pub min_confidence: f32,
 }
 
 pub fn split_bundle(
@@ -22,24 +31,179 @@ pub fn split_bundle(
     bundle::split_bundle(text, mode, config)
 }
 
+pub fn merge_bundle<S: AsRef<str>>(notes: &[S], delimiter: &str) -> String {
+    bundle::merge_bundle(notes, delimiter)
+}
+
 pub fn extract_candidates(
     text: &str,
     format: NoteFormat,
     config: &Config,
     options: ParseOptions,
 ) -> (Vec<SectionCandidate>, Vec<ParseWarning>) {
-    let normalized = normalize::normalize_text(text);
+    let normalized = normalize::normalize_text_with(text, config.normalize.preserve_bullets);
     let lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
-    let headings = headings::scan_headings(&lines, config);
-    sectionize::extract_sections(&lines, &headings, format, config, options.apply_heuristics)
+    let headings = headings::scan_headings_for(&lines, config, Some(format));
+    let raw_lines = config
+        .normalize
+        .keep_raw_content
+        .then(|| normalize::raw_lines(text));
+    let (mut candidates, mut warnings) = sectionize::extract_sections_with_raw(
+        &lines,
+        raw_lines.as_deref(),
+        &headings,
+        format,
+        config,
+        options.apply_heuristics,
+    );
+    if config.normalize.rewrap {
+        for candidate in &mut candidates {
+            candidate.content = normalize::rewrap(&candidate.content);
+        }
+    }
+    if config.parser.parse_vitals {
+        let objective = config.heading_key(SectionName::Objective.as_str());
+        for candidate in &mut candidates {
+            if config.heading_key(&candidate.name) == objective {
+                let vitals = vitals::extract_vitals(&candidate.content);
+                candidate.vitals = (!vitals.is_empty()).then_some(vitals);
+            }
+        }
+    }
+    if options.min_confidence > 0.0 {
+        candidates.retain(|candidate| {
+            if candidate.confidence >= options.min_confidence {
+                return true;
+            }
+            warnings.push(warnings::warning(
+                "low_confidence_dropped",
+                format!(
+                    "Dropped section {} with confidence {:.2} below {:.2}",
+                    candidate.name, candidate.confidence, options.min_confidence
+                ),
+                candidate.start_line,
+                candidate.end_line,
+                crate::models::WarningSeverity::Info,
+            ));
+            false
+        });
+    }
+    (candidates, warnings)
+}
+
+/// Reorders `note.sections` to `config.section_order(note.format)`, adding an
+/// empty section with confidence 0.0 for each required section the note lacks.
+/// Sections outside the order keep their relative order after it, with the
+/// fallback section last. Required sections come from the format's built-in
+/// template; use `sort_sections_with` for another one.
+pub fn sort_sections(note: &mut StructuredNote, config: &Config) {
+    let rules = TemplateRules::builtin_with(Template::from_format(note.format), config);
+    sort_sections_with(note, &rules, config);
+}
+
+/// Like `sort_sections`, taking required sections from `rules`, e.g. a
+/// `[[templates]]` entry. A group of alternatives gets at most one placeholder,
+/// and none when any alternative is present.
+pub fn sort_sections_with(note: &mut StructuredNote, rules: &TemplateRules, config: &Config) {
+    let present: HashSet<String> = note
+        .sections
+        .iter()
+        .map(|section| config.heading_key(section.canonical()))
+        .collect();
+    let mut missing: Vec<Vec<String>> = rules
+        .required
+        .iter()
+        .map(|group| group.iter().map(|name| config.heading_key(name)).collect())
+        .filter(|group: &Vec<String>| !group.iter().any(|key| present.contains(key)))
+        .collect();
+    let mut take_missing = |key: &str| match missing
+        .iter()
+        .position(|group| group.iter().any(|k| k == key))
+    {
+        Some(idx) => {
+            missing.remove(idx);
+            true
+        }
+        None => false,
+    };
+    let mut remaining = std::mem::take(&mut note.sections);
+    let mut sorted = Vec::new();
+    for name in config.section_order(note.format) {
+        let key = config.heading_key(&name);
+        let (matched, rest): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|section| config.heading_key(section.canonical()) == key);
+        remaining = rest;
+        if matched.is_empty() && take_missing(&key) {
+            sorted.push(crate::models::Section {
+                name,
+                content: String::new(),
+                confidence: 0.0,
+                detected_by: None,
+                resolved_by: None,
+                part: None,
+                canonical_name: None,
+                raw_content: None,
+                raw_heading: None,
+                span: None,
+                vitals: None,
+            });
+        }
+        sorted.extend(matched);
+    }
+    let fallback = config.heading_key(&config.fallback_section_name);
+    let (narrative, other): (Vec<_>, Vec<_>) = remaining
+        .into_iter()
+        .partition(|section| config.heading_key(section.canonical()) == fallback);
+    sorted.extend(other);
+    sorted.extend(narrative);
+
+    note.content_hash = util::content_hash(&sorted);
+    note.confidence = crate::models::ConfidenceSummary::from_sections(&sorted);
+    note.sections = sorted;
+}
+
+/// Outline of the detected sections as `(name, start_line, end_line)`, without
+/// building a `StructuredNote`.
+pub fn list_sections(
+    text: &str,
+    format: NoteFormat,
+    config: &Config,
+    options: ParseOptions,
+) -> Vec<(String, usize, usize)> {
+    let (candidates, _) = extract_candidates(text, format, config, options);
+    candidates
+        .into_iter()
+        .map(|c| (c.name, c.start_line, c.end_line))
+        .collect()
 }
 
 pub fn build_note(
+    candidates: Vec<SectionCandidate>,
+    format: NoteFormat,
+    source_file: Option<String>,
+    note_index: usize,
+    warnings: Vec<ParseWarning>,
+) -> StructuredNote {
+    build_note_with(
+        candidates,
+        format,
+        source_file,
+        note_index,
+        warnings,
+        util::DEFAULT_ID_TEMPLATE,
+    )
+}
+
+/// Like `build_note`, with the note id built from `id_template` (see
+/// `util::expand_id_template`).
+pub fn build_note_with(
     candidates: Vec<SectionCandidate>,
     format: NoteFormat,
     source_file: Option<String>,
     note_index: usize,
     mut warnings: Vec<ParseWarning>,
+    id_template: &str,
 ) -> StructuredNote {
     let mut sections = Vec::new();
     for candidate in candidates {
@@ -52,20 +216,48 @@ pub fn build_note(
                 crate::models::WarningSeverity::Info,
             ));
         }
+        let (name, canonical_name) = match candidate.display_name {
+            Some(display) => (display, Some(candidate.name)),
+            None => (candidate.name, None),
+        };
+        let raw_heading = (!candidate.raw_heading.is_empty()
+            && util::normalize_heading_key(&candidate.raw_heading)
+                != util::normalize_heading_key(&name))
+        .then_some(candidate.raw_heading);
         sections.push(crate::models::Section {
-            name: candidate.name,
+            name,
             content: candidate.content.trim().to_string(),
             confidence: candidate.confidence,
+            detected_by: Some(candidate.detected_by),
+            resolved_by: candidate.resolved_by,
+            part: candidate.part,
+            canonical_name,
+            raw_content: candidate.raw_content,
+            raw_heading,
+            span: Some(crate::models::Span {
+                line_start: candidate.start_line,
+                line_end: candidate.end_line,
+            }),
+            vitals: candidate.vitals,
         });
     }
 
+    let content_hash = util::content_hash(&sections);
+    let confidence = crate::models::ConfidenceSummary::from_sections(&sections);
     StructuredNote {
-        id: format!("note-{}-{}", note_index, util::now_iso()),
+        id: util::expand_id_template(
+            id_template,
+            note_index,
+            source_file.as_deref(),
+            format.as_str(),
+        ),
         format,
         source_file,
         note_index,
         sections,
         warnings,
+        content_hash,
+        confidence,
         metadata: crate::models::Metadata {
             generated_at: util::now_iso(),
             tool_version: env!("CARGO_PKG_VERSION").to_string(),
@@ -82,7 +274,14 @@ pub fn parse_note(
     options: ParseOptions,
 ) -> StructuredNote {
     let (candidates, warnings) = extract_candidates(text, format, config, options);
-    build_note(candidates, format, source_file, note_index, warnings)
+    build_note_with(
+        candidates,
+        format,
+        source_file,
+        note_index,
+        warnings,
+        &config.id_template,
+    )
 }
 
 pub fn parse_notes(
@@ -101,12 +300,13 @@ pub fn parse_notes(
             let (mut candidates, mut warnings) =
                 extract_candidates(&note_text, format, config, options);
             warnings.extend(bundle_warnings.clone());
-            build_note(
-                candidates.drain(..).collect(),
+            build_note_with(
+                std::mem::take(&mut candidates),
                 format,
                 source_file.clone(),
                 note_offset + idx + 1,
                 warnings,
+                &config.id_template,
             )
         })
         .collect()
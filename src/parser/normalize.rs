@@ -1,11 +1,106 @@
+/// Columns between tab stops when leading tabs are expanded.
+pub const TAB_WIDTH: usize = 4;
+
+/// Unifies line endings, trims trailing whitespace and rewrites bullets to
+/// `- `. Leading indentation is kept, with tabs expanded to `TAB_WIDTH`
+/// stops, so nesting under a heading survives into section content and
+/// `indent_width`; other tabs become single spaces.
 pub fn normalize_text(input: &str) -> String {
-    let mut text = input.replace("\r\n", "\n").replace('\r', "\n");
-    text = text.replace('\t', " ");
+    normalize_text_with(input, false)
+}
+
+/// Like `normalize_text`; `preserve_bullets` leaves `* ` and `\u{2022}` markers
+/// as written (`normalize.preserve_bullets`).
+pub fn normalize_text_with(input: &str, preserve_bullets: bool) -> String {
+    // Also dropped by `util::read_to_string`; see there for why.
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let text = input.replace("\r\n", "\n").replace('\r', "\n");
     let mut lines = Vec::new();
     for line in text.lines() {
-        let trimmed = line.trim_end();
-        let replaced = trimmed.replace('\u{2022}', "-").replace("* ", "- ");
-        lines.push(replaced);
+        let body = line.trim_start_matches([' ', '\t']);
+        let indented = format!(
+            "{}{}",
+            " ".repeat(indent_width(line)),
+            body.replace('\t', " ")
+        );
+        let trimmed = indented.trim_end();
+        if preserve_bullets {
+            lines.push(trimmed.to_string());
+        } else {
+            lines.push(trimmed.replace('\u{2022}', "-").replace("* ", "- "));
+        }
     }
     lines.join("\n")
 }
+
+/// Splits `input` into lines that keep their original terminators (`\n`,
+/// `\r\n` or a lone `\r`), numbered the same way as `normalize_text` output.
+pub fn raw_lines(input: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            continue;
+        }
+        if c == '\n' || c == '\r' {
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Rejoins hard-wrapped prose: consecutive non-bullet lines are joined with a
+/// space unless the previous line ends a sentence. Bullets and blank-line
+/// paragraph breaks are kept as they are.
+pub fn rewrap(content: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut joinable = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            out.push(String::new());
+            joinable = false;
+            continue;
+        }
+        let bullet = is_bullet(trimmed);
+        match out.last_mut() {
+            Some(last) if joinable && !bullet => {
+                last.push(' ');
+                last.push_str(trimmed);
+            }
+            _ => out.push(line.trim_end().to_string()),
+        }
+        joinable = !bullet && !trimmed.ends_with(['.', '!', '?', ':', ';']);
+    }
+    out.join("\n")
+}
+
+/// Indentation depth of a line in columns, with tabs advancing to the next
+/// `TAB_WIDTH` stop.
+pub fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += TAB_WIDTH - width % TAB_WIDTH,
+            _ => break,
+        }
+    }
+    width
+}
+
+fn is_bullet(line: &str) -> bool {
+    if ["- ", "+ ", "* ", "\u{2022}"]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+    {
+        return true;
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
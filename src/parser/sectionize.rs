@@ -1,20 +1,41 @@
 use crate::config::Config;
-use crate::models::{HeadingLine, NoteFormat, ParseWarning, SectionCandidate, WarningSeverity};
+use crate::models::{
+    DuplicatePolicy, HeadingLine, HeadingMethod, NoteFormat, ParseWarning, SectionCandidate,
+    WarningSeverity,
+};
 use crate::parser::headings;
+use crate::parser::normalize;
 use crate::parser::warnings;
 use crate::util;
-use once_cell::sync::Lazy;
 use regex::Regex;
 
-static FALLBACK_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?i)^(?P<h>[A-Za-z /&.-]{2,40})\s*[:\-]\s*(?P<rest>.+)$").unwrap());
-
 pub fn extract_sections(
     lines: &[String],
     headings_found: &[HeadingLine],
     format: NoteFormat,
     config: &Config,
     apply_heuristics: bool,
+) -> (Vec<SectionCandidate>, Vec<ParseWarning>) {
+    extract_sections_with_raw(
+        lines,
+        None,
+        headings_found,
+        format,
+        config,
+        apply_heuristics,
+    )
+}
+
+/// Like `extract_sections`, but also fills each candidate's `raw_content`
+/// from `raw_lines`, the un-normalized input split by `normalize::raw_lines`
+/// so its indices line up with `lines`.
+pub fn extract_sections_with_raw(
+    lines: &[String],
+    raw_lines: Option<&[String]>,
+    headings_found: &[HeadingLine],
+    format: NoteFormat,
+    config: &Config,
+    apply_heuristics: bool,
 ) -> (Vec<SectionCandidate>, Vec<ParseWarning>) {
     let mut warnings_list = Vec::new();
     let mut headings = headings_found.to_vec();
@@ -22,7 +43,7 @@ pub fn extract_sections(
 
     if headings.is_empty() {
         if apply_heuristics {
-            headings = fallback_headings(lines, config);
+            headings = fallback_headings(lines, config, format);
             if !headings.is_empty() {
                 used_fallback = true;
                 warnings_list.push(warnings::warning(
@@ -37,19 +58,35 @@ pub fn extract_sections(
         if headings.is_empty() {
             warnings_list.push(warnings::warning(
                 "no_headings",
-                "No headings detected; content grouped as Narrative".to_string(),
+                format!(
+                    "No headings detected; content grouped as {}",
+                    config.fallback_section_name
+                ),
                 1,
                 lines.len().max(1),
                 WarningSeverity::Warning,
             ));
             let content = lines.join("\n").trim().to_string();
+            if let Some(limit) = config.parser.narrative_chunk_chars {
+                if content.chars().count() > limit {
+                    let chunks =
+                        narrative_chunks(lines, raw_lines, limit, &config.fallback_section_name);
+                    return (chunks, warnings_list);
+                }
+            }
             let candidate = SectionCandidate {
-                name: "Narrative".to_string(),
-                raw_heading: "Narrative".to_string(),
+                name: config.fallback_section_name.clone(),
+                raw_heading: config.fallback_section_name.clone(),
                 content,
                 start_line: 1,
                 end_line: lines.len().max(1),
                 confidence: 0.4,
+                detected_by: HeadingMethod::NoHeading,
+                resolved_by: None,
+                part: None,
+                display_name: None,
+                raw_content: raw_slice(raw_lines, 1, lines.len()),
+                vitals: None,
             };
             return (vec![candidate], warnings_list);
         }
@@ -72,47 +109,61 @@ pub fn extract_sections(
         if let Some(inline) = &heading.inline_content {
             content_lines.push(inline.clone());
         }
-        let content_start = heading.line_num + 1;
+        // A setext heading's underline is part of the heading, not content.
+        let content_start = match heading.method {
+            HeadingMethod::Setext => heading.line_num + 2,
+            _ => heading.line_num + 1,
+        };
         for line_idx in content_start..=end_line {
             if let Some(line) = lines.get(line_idx - 1) {
                 content_lines.push(line.clone());
             }
         }
 
-        let (name, mapped) = map_heading(&heading.heading, &section_order);
+        let (name, mapped) = map_heading(&heading.heading, &section_order, config);
         if !mapped {
-            warnings_list.push(warnings::warning(
-                "unmapped_heading",
-                format!("Heading '{}' not in target format", heading.heading),
+            warnings_list.push(warnings::unmapped_heading(
+                raw_heading_text(heading),
                 start_line,
                 end_line,
-                WarningSeverity::Info,
             ));
         }
 
         let confidence = if used_fallback { 0.6 } else { 0.85 };
         let candidate = SectionCandidate {
             name,
-            raw_heading: heading.heading.clone(),
+            raw_heading: raw_heading_text(heading).to_string(),
             content: content_lines.join("\n").trim().to_string(),
             start_line,
             end_line,
             confidence,
+            detected_by: heading.method,
+            resolved_by: Some(heading.source.clone()),
+            part: None,
+            display_name: (mapped && config.parser.preserve_heading_case)
+                .then(|| raw_heading_text(heading).to_string()),
+            raw_content: raw_slice(raw_lines, start_line, end_line),
+            vitals: None,
         };
         candidates.push(candidate);
     }
 
     let mut ordered = Vec::new();
     for name in section_order {
-        let key = util::normalize_heading_key(&name);
-        for candidate in &candidates {
-            if util::normalize_heading_key(&candidate.name) == key {
-                ordered.push(candidate.clone());
-            }
-        }
+        let key = config.heading_key(&name);
+        let group: Vec<SectionCandidate> = candidates
+            .iter()
+            .filter(|candidate| config.heading_key(&candidate.name) == key)
+            .cloned()
+            .collect();
+        ordered.extend(resolve_duplicates(
+            group,
+            config.parser.effective_duplicate_policy(),
+        ));
     }
     for candidate in candidates {
-        if util::normalize_heading_key(&candidate.name) == util::normalize_heading_key("Narrative")
+        if util::normalize_heading_key(&candidate.name)
+            == util::normalize_heading_key(&config.fallback_section_name)
         {
             ordered.push(candidate);
         }
@@ -121,28 +172,226 @@ pub fn extract_sections(
     (ordered, warnings_list)
 }
 
-fn map_heading(heading: &str, section_order: &[String]) -> (String, bool) {
-    let heading_key = util::normalize_heading_key(heading);
+/// Indentation depth of each non-blank body line of a candidate, as
+/// `(line_num, width)`, relative to the shallowest line so a uniformly
+/// indented block reads as depth 0. The heading line itself is skipped.
+pub fn content_indentation(lines: &[String], candidate: &SectionCandidate) -> Vec<(usize, usize)> {
+    let first = if candidate.detected_by == HeadingMethod::NoHeading {
+        candidate.start_line
+    } else {
+        candidate.start_line + 1
+    };
+    let widths: Vec<(usize, usize)> = (first..=candidate.end_line)
+        .filter_map(|line_num| {
+            let line = lines.get(line_num.checked_sub(1)?)?;
+            if line.trim().is_empty() {
+                None
+            } else {
+                Some((line_num, normalize::indent_width(line)))
+            }
+        })
+        .collect();
+    let base = widths.iter().map(|(_, w)| *w).min().unwrap_or(0);
+    widths
+        .into_iter()
+        .map(|(line_num, width)| (line_num, width - base))
+        .collect()
+}
+
+/// Splits unstructured text on blank lines, packing paragraphs into numbered
+/// parts named `name` (the fallback section) of roughly `limit` characters each.
+fn narrative_chunks(
+    lines: &[String],
+    raw_lines: Option<&[String]>,
+    limit: usize,
+    name: &str,
+) -> Vec<SectionCandidate> {
+    let mut paragraphs: Vec<(usize, usize)> = Vec::new();
+    let mut start = None;
+    for (idx, line) in lines.iter().enumerate() {
+        match (line.trim().is_empty(), start) {
+            (false, None) => start = Some(idx),
+            (true, Some(s)) => {
+                paragraphs.push((s, idx - 1));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        paragraphs.push((s, lines.len() - 1));
+    }
+
+    let mut chunks: Vec<(usize, usize)> = Vec::new();
+    for (p_start, p_end) in paragraphs {
+        let joined_len = |from: usize, to: usize| {
+            lines[from..=to]
+                .iter()
+                .map(|l| l.chars().count() + 1)
+                .sum::<usize>()
+        };
+        match chunks.last_mut() {
+            Some(last) if joined_len(last.0, p_end) <= limit => last.1 = p_end,
+            _ => chunks.push((p_start, p_end)),
+        }
+    }
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (from, to))| SectionCandidate {
+            name: name.to_string(),
+            raw_heading: name.to_string(),
+            content: lines[from..=to].join("\n").trim().to_string(),
+            start_line: from + 1,
+            end_line: to + 1,
+            confidence: 0.4,
+            detected_by: HeadingMethod::NoHeading,
+            resolved_by: None,
+            part: Some(idx + 1),
+            display_name: None,
+            raw_content: raw_slice(raw_lines, from + 1, to + 1),
+            vitals: None,
+        })
+        .collect()
+}
+
+/// Applies `policy` to candidates sharing a canonical name. `Merge` joins them
+/// into one candidate with the lowest confidence of its parts; `KeepHighest`
+/// keeps the most confident one.
+pub fn resolve_duplicates(
+    group: Vec<SectionCandidate>,
+    policy: DuplicatePolicy,
+) -> Vec<SectionCandidate> {
+    if group.len() <= 1 {
+        return group;
+    }
+    match policy {
+        DuplicatePolicy::KeepAll => group,
+        DuplicatePolicy::KeepHighest => {
+            let mut best = group[0].clone();
+            for candidate in &group[1..] {
+                if candidate.confidence > best.confidence {
+                    best = candidate.clone();
+                }
+            }
+            vec![best]
+        }
+        DuplicatePolicy::Merge => {
+            let mut merged = group[0].clone();
+            let parts: Vec<&str> = group
+                .iter()
+                .map(|c| c.content.as_str())
+                .filter(|content| !content.is_empty())
+                .collect();
+            merged.content = parts.join("\n\n");
+            if merged.raw_content.is_some() {
+                let raw: Vec<&str> = group
+                    .iter()
+                    .filter_map(|c| c.raw_content.as_deref())
+                    .collect();
+                merged.raw_content = Some(raw.join("\n\n"));
+            }
+            // The merged span covers every part; it is only as trustworthy as
+            // its weakest part, so confidence is the minimum, not the maximum.
+            merged.start_line = group
+                .iter()
+                .map(|c| c.start_line)
+                .min()
+                .unwrap_or(merged.start_line);
+            merged.end_line = group
+                .iter()
+                .map(|c| c.end_line)
+                .max()
+                .unwrap_or(merged.end_line);
+            merged.confidence = group
+                .iter()
+                .map(|c| c.confidence)
+                .fold(merged.confidence, f32::min);
+            vec![merged]
+        }
+    }
+}
+
+/// Original lines `start..=end` (1-based) verbatim, minus the final line break.
+fn raw_slice(raw_lines: Option<&[String]>, start: usize, end: usize) -> Option<String> {
+    let raw_lines = raw_lines?;
+    let from = start.saturating_sub(1).min(raw_lines.len());
+    let to = end.min(raw_lines.len()).max(from);
+    let joined = raw_lines[from..to].concat();
+    let trimmed = joined
+        .strip_suffix("\r\n")
+        .or_else(|| joined.strip_suffix('\n'))
+        .or_else(|| joined.strip_suffix('\r'))
+        .unwrap_or(&joined);
+    Some(trimmed.to_string())
+}
+
+/// The heading as written in the source line, without a Markdown or list
+/// prefix, inline content or the trailing separator.
+fn raw_heading_text(heading: &HeadingLine) -> &str {
+    let mut text = heading.raw.trim();
+    if let Some(inline) = &heading.inline_content {
+        text = text.strip_suffix(inline.as_str()).unwrap_or(text);
+    }
+    headings::strip_ordinal(text.trim_start_matches('#').trim_start())
+        .trim_end_matches(|c: char| !c.is_alphanumeric() && c != ')')
+}
+
+fn map_heading(heading: &str, section_order: &[String], config: &Config) -> (String, bool) {
+    let heading_key = config.heading_key(heading);
     for name in section_order {
-        if util::normalize_heading_key(name) == heading_key {
+        if config.heading_key(name) == heading_key {
             return (name.clone(), true);
         }
     }
-    ("Narrative".to_string(), false)
+    (config.fallback_section_name.clone(), false)
 }
 
-fn fallback_headings(lines: &[String], config: &Config) -> Vec<HeadingLine> {
+/// Builds the `Heading <sep> rest` pattern from the configured separators,
+/// trying longer separators first so `=>` wins over `=`.
+fn fallback_regex(separators: &[String]) -> Option<Regex> {
+    let mut seps: Vec<&str> = separators
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if seps.is_empty() {
+        return None;
+    }
+    seps.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let alternation = seps
+        .iter()
+        .map(|s| regex::escape(s))
+        .collect::<Vec<_>>()
+        .join("|");
+    Regex::new(&format!(
+        r"(?i)^(?P<h>[A-Za-z /&.-]{{2,40}})\s*(?:{})\s*(?P<rest>.+)$",
+        alternation
+    ))
+    .ok()
+}
+
+fn fallback_headings(lines: &[String], config: &Config, format: NoteFormat) -> Vec<HeadingLine> {
     let mut headings = Vec::new();
-    for (idx, line) in lines.iter().enumerate() {
-        if let Some(caps) = FALLBACK_RE.captures(line.trim()) {
+    let Some(fallback_re) = fallback_regex(&config.parser.fallback_separators) else {
+        return headings;
+    };
+    let skip = headings::demographics_block_len(lines, config);
+    for (idx, line) in lines.iter().enumerate().skip(skip) {
+        if let Some(caps) = fallback_re.captures(line.trim()) {
             let raw = caps.name("h").map(|m| m.as_str()).unwrap_or("");
             let rest = caps.name("rest").map(|m| m.as_str()).unwrap_or("");
-            if let Some(mapped) = headings::canonicalize_heading(raw, config) {
+            if let Some((mapped, source)) =
+                headings::canonicalize_heading_with_source_for(raw, config, Some(format))
+            {
                 headings.push(HeadingLine {
                     line_num: idx + 1,
                     raw: line.clone(),
                     heading: mapped,
                     inline_content: Some(rest.trim().to_string()),
+                    method: HeadingMethod::Fallback,
+                    source,
                 });
             }
         }
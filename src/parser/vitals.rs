@@ -0,0 +1,34 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// `(key, pattern)` pairs; each pattern captures the reading in `v`.
+static VITAL_RES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("bp", r"(?i)\b(?:BP|B/P|blood pressure)\s*:?\s*(?P<v>\d{2,3}\s*/\s*\d{2,3})"),
+        ("hr", r"(?i)\b(?:HR|pulse|heart rate)\s*:?\s*(?P<v>\d{2,3})\b"),
+        ("rr", r"(?i)\b(?:RR|resp(?:iratory)? rate)\s*:?\s*(?P<v>\d{1,2})\b"),
+        (
+            "temp",
+            r"(?i)\b(?:T|temp(?:erature)?)\s*:?\s*(?P<v>\d{2,3}(?:\.\d+)?\s*(?:°\s*)?[CF]?)(?:\b|$)",
+        ),
+        ("spo2", r"(?i)\b(?:SpO2|O2 sat|sat)\s*:?\s*(?P<v>\d{2,3}\s*%)"),
+    ]
+    .into_iter()
+    .map(|(key, pattern)| (key, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// Finds common vital-sign readings (`BP 120/80 HR 72 RR 16 T 98.6`) in
+/// section content. The first reading of each kind wins; values keep their
+/// units but have inner whitespace removed (`120 / 80` gives `120/80`).
+pub fn extract_vitals(content: &str) -> BTreeMap<String, String> {
+    let mut vitals = BTreeMap::new();
+    for (key, re) in VITAL_RES.iter() {
+        if let Some(value) = re.captures(content).and_then(|caps| caps.name("v")) {
+            let value: String = value.as_str().split_whitespace().collect();
+            vitals.insert(key.to_string(), value);
+        }
+    }
+    vitals
+}
@@ -13,5 +13,29 @@ pub fn warning(
         line_start,
         line_end,
         severity,
+        heading: None,
     }
 }
+
+const UNMAPPED_HEADING: &str = "unmapped_heading";
+
+pub fn unmapped_heading(raw_heading: &str, line_start: usize, line_end: usize) -> ParseWarning {
+    ParseWarning {
+        heading: Some(raw_heading.to_string()),
+        ..warning(
+            UNMAPPED_HEADING,
+            format!("Heading '{}' not in target format", raw_heading),
+            line_start,
+            line_end,
+            WarningSeverity::Info,
+        )
+    }
+}
+
+/// The heading text of an `unmapped_heading` warning.
+pub fn unmapped_heading_text(warning: &ParseWarning) -> Option<&str> {
+    if warning.code != UNMAPPED_HEADING {
+        return None;
+    }
+    warning.heading.as_deref()
+}
@@ -1,16 +1,82 @@
-use crate::models::{CsvLayout, NoteFormat, StructuredNote};
+use crate::config::CsvConfig;
+use crate::models::{CsvLayout, StructuredNote};
 use anyhow::Result;
-use csv::Writer;
+use csv::{QuoteStyle, Writer, WriterBuilder};
 use std::collections::HashSet;
 
+#[derive(Debug, Clone, Copy)]
+enum Dialect {
+    Csv,
+    Tsv,
+}
+
+impl Dialect {
+    fn writer(self) -> Writer<Vec<u8>> {
+        match self {
+            Dialect::Csv => Writer::from_writer(vec![]),
+            // RFC 4180 quoting with a tab delimiter: commas stay bare, and only
+            // fields holding tabs, quotes or line breaks are quoted.
+            Dialect::Tsv => WriterBuilder::new()
+                .delimiter(b'\t')
+                .quote_style(QuoteStyle::Necessary)
+                .from_writer(vec![]),
+        }
+    }
+}
+
 pub fn render_notes(notes: &[StructuredNote], layout: CsvLayout) -> Result<String> {
+    render_with(notes, layout, Dialect::Csv, "")
+}
+
+pub fn render_notes_tsv(notes: &[StructuredNote], layout: CsvLayout) -> Result<String> {
+    render_with(notes, layout, Dialect::Tsv, "")
+}
+
+/// Like `render_notes`, but honors `[csv]` settings such as `missing_marker`.
+pub fn render_notes_with(
+    notes: &[StructuredNote],
+    layout: CsvLayout,
+    csv: &CsvConfig,
+) -> Result<String> {
+    render_with(notes, layout, Dialect::Csv, &csv.missing_marker)
+}
+
+pub fn render_notes_tsv_with(
+    notes: &[StructuredNote],
+    layout: CsvLayout,
+    csv: &CsvConfig,
+) -> Result<String> {
+    render_with(notes, layout, Dialect::Tsv, &csv.missing_marker)
+}
+
+fn render_with(
+    notes: &[StructuredNote],
+    layout: CsvLayout,
+    dialect: Dialect,
+    missing_marker: &str,
+) -> Result<String> {
     match layout {
-        CsvLayout::Wide => render_wide(notes),
-        CsvLayout::Long => render_long(notes),
+        CsvLayout::Wide => render_wide(notes, dialect, missing_marker),
+        CsvLayout::Long => render_long(notes, dialect),
+    }
+}
+
+/// Picks the layout for these notes, honoring `[csv.by_format]` overrides.
+/// Returns `None` when the notes mix formats so no single override applies.
+pub fn resolve_layout(notes: &[StructuredNote], csv: &CsvConfig) -> Option<CsvLayout> {
+    let mut formats = notes.iter().map(|note| note.format);
+    match formats.next() {
+        Some(first) if formats.all(|format| format == first) => {
+            Some(csv.by_format.get(&first).copied().unwrap_or(csv.layout))
+        }
+        Some(_) => None,
+        None => Some(csv.layout),
     }
 }
 
-fn render_wide(notes: &[StructuredNote]) -> Result<String> {
+/// One row per note and one column per section name. A section the note
+/// lacks is written as `missing_marker`; a present but empty one stays empty.
+fn render_wide(notes: &[StructuredNote], dialect: Dialect, missing_marker: &str) -> Result<String> {
     let mut seen = HashSet::new();
     let mut section_names = Vec::new();
     for note in notes {
@@ -21,7 +87,7 @@ fn render_wide(notes: &[StructuredNote]) -> Result<String> {
         }
     }
 
-    let mut wtr = Writer::from_writer(vec![]);
+    let mut wtr = dialect.writer();
     let mut header = vec!["id", "format", "source_file", "note_index"]
         .into_iter()
         .map(|s| s.to_string())
@@ -30,11 +96,12 @@ fn render_wide(notes: &[StructuredNote]) -> Result<String> {
     wtr.write_record(&header)?;
 
     for note in notes {
-        let mut record = Vec::new();
-        record.push(note.id.clone());
-        record.push(format_label(note.format).to_string());
-        record.push(note.source_file.clone().unwrap_or_default());
-        record.push(note.note_index.to_string());
+        let mut record = vec![
+            note.id.clone(),
+            note.format.as_str().to_string(),
+            note.source_file.clone().unwrap_or_default(),
+            note.note_index.to_string(),
+        ];
 
         for name in &section_names {
             let value = note
@@ -42,7 +109,7 @@ fn render_wide(notes: &[StructuredNote]) -> Result<String> {
                 .iter()
                 .find(|s| &s.name == name)
                 .map(|s| s.content.clone())
-                .unwrap_or_default();
+                .unwrap_or_else(|| missing_marker.to_string());
             record.push(value);
         }
         wtr.write_record(&record)?;
@@ -52,8 +119,8 @@ fn render_wide(notes: &[StructuredNote]) -> Result<String> {
     Ok(String::from_utf8(data)?)
 }
 
-fn render_long(notes: &[StructuredNote]) -> Result<String> {
-    let mut wtr = Writer::from_writer(vec![]);
+fn render_long(notes: &[StructuredNote], dialect: Dialect) -> Result<String> {
+    let mut wtr = dialect.writer();
     wtr.write_record([
         "note_id",
         "format",
@@ -67,11 +134,11 @@ fn render_long(notes: &[StructuredNote]) -> Result<String> {
         for section in &note.sections {
             wtr.write_record([
                 note.id.as_str(),
-                format_label(note.format),
+                note.format.as_str(),
                 note.source_file.as_deref().unwrap_or(""),
                 &note.note_index.to_string(),
-                section.name.as_str(),
-                section.content.as_str(),
+                &section.name,
+                &section.content,
             ])?;
         }
     }
@@ -79,11 +146,3 @@ fn render_long(notes: &[StructuredNote]) -> Result<String> {
     let data = wtr.into_inner()?;
     Ok(String::from_utf8(data)?)
 }
-
-fn format_label(format: NoteFormat) -> &'static str {
-    match format {
-        NoteFormat::Soap => "soap",
-        NoteFormat::Hp => "hp",
-        NoteFormat::Discharge => "discharge",
-    }
-}
@@ -0,0 +1,80 @@
+use crate::models::{NoteFormat, StructuredNote};
+use crate::render::html;
+use anyhow::Result;
+use serde_json::{json, Value};
+
+/// FHIR R4 `Composition` resources, one per note, with one `section` per
+/// note section. Several notes are wrapped in a `collection` Bundle.
+pub fn render_notes(notes: &[StructuredNote]) -> Result<String> {
+    if notes.len() == 1 {
+        return Ok(serde_json::to_string_pretty(&composition(&notes[0]))?);
+    }
+    let entries: Vec<Value> = notes
+        .iter()
+        .map(|note| json!({ "resource": composition(note) }))
+        .collect();
+    let bundle = json!({
+        "resourceType": "Bundle",
+        "type": "collection",
+        "entry": entries,
+    });
+    Ok(serde_json::to_string_pretty(&bundle)?)
+}
+
+pub fn composition(note: &StructuredNote) -> Value {
+    let (code, display) = loinc_type(note.format);
+    let sections: Vec<Value> = note
+        .sections
+        .iter()
+        .map(|section| {
+            json!({
+                "title": section.name,
+                "text": {
+                    "status": "generated",
+                    "div": format!(
+                        "<div xmlns=\"http://www.w3.org/1999/xhtml\">{}</div>",
+                        html::escape(&section.content)
+                    ),
+                },
+            })
+        })
+        .collect();
+    json!({
+        "resourceType": "Composition",
+        "id": resource_id(&note.id),
+        "meta": { "lastUpdated": note.metadata.generated_at },
+        "identifier": { "system": "urn:clinote:note-id", "value": note.id },
+        "status": "final",
+        "type": {
+            "coding": [{ "system": "http://loinc.org", "code": code, "display": display }],
+        },
+        "date": note.metadata.generated_at,
+        "author": [{ "display": format!("clinote {}", note.metadata.tool_version) }],
+        "title": format!("{} note {}", display, note.note_index),
+        "section": sections,
+    })
+}
+
+fn loinc_type(format: NoteFormat) -> (&'static str, &'static str) {
+    match format {
+        NoteFormat::Soap | NoteFormat::Progress => ("11506-3", "Progress note"),
+        NoteFormat::Hp => ("34117-2", "History and physical note"),
+        NoteFormat::Discharge => ("18842-5", "Discharge summary"),
+        NoteFormat::Operative => ("11504-8", "Surgical operation note"),
+    }
+}
+
+/// FHIR ids allow only `[A-Za-z0-9-.]` and at most 64 characters; the full
+/// note id is kept in `identifier`.
+fn resource_id(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(64)
+        .collect()
+}
@@ -0,0 +1,93 @@
+use crate::models::StructuredNote;
+use anyhow::Result;
+use std::collections::HashMap;
+
+pub fn render_notes(notes: &[StructuredNote]) -> Result<String> {
+    let mut out = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html>".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        "<title>Structured Notes</title>".to_string(),
+        "</head>".to_string(),
+        "<body>".to_string(),
+    ];
+    for (idx, note) in notes.iter().enumerate() {
+        let note_id = format!("note-{}", idx + 1);
+        out.push(format!(
+            "<article id=\"{}\" data-format=\"{}\">",
+            note_id,
+            note.format.as_str()
+        ));
+        out.push(format!("<h1>Structured Note {}</h1>", idx + 1));
+        if let Some(source) = &note.source_file {
+            out.push(format!("<p>Source: {}</p>", escape(source)));
+        }
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for section in &note.sections {
+            let slug = slugify(&section.name);
+            let count = seen.entry(slug.clone()).or_insert(0);
+            *count += 1;
+            let anchor = if *count == 1 {
+                format!("{}-{}", note_id, slug)
+            } else {
+                format!("{}-{}-{}", note_id, slug, count)
+            };
+            let mut attrs = format!(
+                "id=\"{}\" data-confidence=\"{:.2}\"",
+                anchor, section.confidence
+            );
+            if let Some(method) = section.detected_by {
+                attrs.push_str(&format!(" data-detected-by=\"{}\"", method.as_str()));
+            }
+            if let Some(span) = &section.span {
+                attrs.push_str(&format!(
+                    " data-lines=\"{}-{}\"",
+                    span.line_start, span.line_end
+                ));
+            }
+            out.push(format!("<section {}>", attrs));
+            out.push(format!("<h2>{}</h2>", escape(&section.name)));
+            out.push(format!("<pre>{}</pre>", escape(&section.content)));
+            out.push("</section>".to_string());
+        }
+        out.push("</article>".to_string());
+    }
+    out.push("</body>".to_string());
+    out.push("</html>".to_string());
+    Ok(out.join("\n"))
+}
+
+/// Escapes text for use in element content and double-quoted attributes.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Lowercase ASCII anchor for a section name (`Follow-up` -> `follow-up`).
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
+}
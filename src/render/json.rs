@@ -1,10 +1,40 @@
-use crate::models::StructuredNote;
+use crate::config::JsonConfig;
+use crate::models::{StructuredNote, SCHEMA_VERSION};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The `json.wrap_envelope` layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub schema_version: String,
+    pub notes: Vec<StructuredNote>,
+}
 
 pub fn render_notes(notes: &[StructuredNote]) -> Result<String> {
-    if notes.len() == 1 {
+    render_notes_with(notes, &JsonConfig::default())
+}
+
+pub fn render_notes_with(notes: &[StructuredNote], config: &JsonConfig) -> Result<String> {
+    if config.wrap_envelope {
+        let envelope = Envelope {
+            schema_version: SCHEMA_VERSION.to_string(),
+            notes: notes.to_vec(),
+        };
+        Ok(serde_json::to_string_pretty(&envelope)?)
+    } else if notes.len() == 1 {
         Ok(serde_json::to_string_pretty(&notes[0])?)
     } else {
         Ok(serde_json::to_string_pretty(&notes)?)
     }
 }
+
+/// Reads JSON output back: an envelope, an array of notes, or a single note.
+pub fn parse_notes(content: &str) -> serde_json::Result<Vec<StructuredNote>> {
+    if let Ok(envelope) = serde_json::from_str::<Envelope>(content) {
+        return Ok(envelope.notes);
+    }
+    if let Ok(many) = serde_json::from_str::<Vec<StructuredNote>>(content) {
+        return Ok(many);
+    }
+    serde_json::from_str::<StructuredNote>(content).map(|one| vec![one])
+}
@@ -1,6 +1,21 @@
-use crate::models::StructuredNote;
+use crate::models::{ConfidenceSummary, Metadata, NoteFormat, Section, StructuredNote};
+use crate::util;
+use anyhow::{anyhow, bail, Result};
+use clap::ValueEnum;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static NOTE_HEADER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^# Structured Note \d+\s*$").unwrap());
+static RAW_HEADING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+?) \((.+)\)$").unwrap());
+
+pub const DEFAULT_NOTE_SEPARATOR: &str = "---";
 
 pub fn render_notes(notes: &[StructuredNote]) -> String {
+    render_notes_with_separator(notes, DEFAULT_NOTE_SEPARATOR)
+}
+
+pub fn render_notes_with_separator(notes: &[StructuredNote], separator: &str) -> String {
     let mut out = Vec::new();
     for (idx, note) in notes.iter().enumerate() {
         out.push(format!("# Structured Note {}", idx + 1));
@@ -10,7 +25,10 @@ pub fn render_notes(notes: &[StructuredNote]) -> String {
         }
         out.push(String::new());
         for section in &note.sections {
-            out.push(format!("## {}", section.name));
+            match &section.raw_heading {
+                Some(raw) => out.push(format!("## {} ({})", section.name, raw)),
+                None => out.push(format!("## {}", section.name)),
+            }
             if section.content.is_empty() {
                 out.push("(empty)".to_string());
             } else {
@@ -19,9 +37,143 @@ pub fn render_notes(notes: &[StructuredNote]) -> String {
             out.push(String::new());
         }
         if idx + 1 < notes.len() {
-            out.push("---".to_string());
+            out.push(separator.to_string());
             out.push(String::new());
         }
     }
     out.join("\n")
 }
+
+/// Reads Markdown produced by [`render_notes`] back into notes, so hand edits
+/// can be re-ingested. Confidence isn't rendered, so every section gets 1.0.
+pub fn parse_markdown(md: &str) -> Result<Vec<StructuredNote>> {
+    parse_markdown_with_separator(md, DEFAULT_NOTE_SEPARATOR)
+}
+
+pub fn parse_markdown_with_separator(md: &str, separator: &str) -> Result<Vec<StructuredNote>> {
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    for (idx, line) in md.lines().enumerate() {
+        if NOTE_HEADER_RE.is_match(line) {
+            blocks.push(Vec::new());
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) => block.push(line),
+            None if line.trim().is_empty() => {}
+            None => bail!(
+                "Expected a `# Structured Note N` heading before line {}",
+                idx + 1
+            ),
+        }
+    }
+    if blocks.is_empty() {
+        bail!("No `# Structured Note N` headings found");
+    }
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(idx, lines)| parse_note_block(lines, idx + 1, separator))
+        .collect()
+}
+
+fn parse_note_block(lines: &[&str], note_index: usize, separator: &str) -> Result<StructuredNote> {
+    let mut format = None;
+    let mut source_file = None;
+    let mut sections = Vec::new();
+    let mut current: Option<(String, Option<String>, Vec<&str>)> = None;
+    for line in lines {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if let Some((name, raw_heading, body)) = current.take() {
+                sections.push(section_from_body(name, raw_heading, &body, separator));
+            }
+            let (name, raw_heading) = split_heading(heading.trim());
+            current = Some((name, raw_heading, Vec::new()));
+            continue;
+        }
+        if let Some((_, _, body)) = current.as_mut() {
+            body.push(line);
+        } else if let Some(value) = line.strip_prefix("Format: ") {
+            format = Some(NoteFormat::from_str(value.trim(), true).map_err(|_| {
+                anyhow!(
+                    "Structured Note {}: unknown format {}",
+                    note_index,
+                    value.trim()
+                )
+            })?);
+        } else if let Some(value) = line.strip_prefix("Source: ") {
+            source_file = Some(value.trim().to_string());
+        }
+    }
+    if let Some((name, raw_heading, body)) = current {
+        sections.push(section_from_body(name, raw_heading, &body, separator));
+    }
+    let format =
+        format.ok_or_else(|| anyhow!("Structured Note {} has no Format line", note_index))?;
+
+    let content_hash = util::content_hash(&sections);
+    let confidence = ConfidenceSummary::from_sections(&sections);
+    Ok(StructuredNote {
+        id: util::expand_id_template(
+            util::DEFAULT_ID_TEMPLATE,
+            note_index,
+            source_file.as_deref(),
+            format.as_str(),
+        ),
+        format,
+        source_file,
+        note_index,
+        sections,
+        warnings: Vec::new(),
+        content_hash,
+        confidence,
+        metadata: Metadata {
+            generated_at: util::now_iso(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+    })
+}
+
+/// `Name (raw heading)` as rendered when the source heading differed.
+fn split_heading(heading: &str) -> (String, Option<String>) {
+    match RAW_HEADING_RE.captures(heading) {
+        Some(caps) => (caps[1].to_string(), Some(caps[2].to_string())),
+        None => (heading.to_string(), None),
+    }
+}
+
+fn section_from_body(
+    name: String,
+    raw_heading: Option<String>,
+    body: &[&str],
+    separator: &str,
+) -> Section {
+    let mut body = body.to_vec();
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+    // The last section of every note but the final one is followed by the separator.
+    if body
+        .last()
+        .is_some_and(|line| line.trim() == separator.trim())
+    {
+        body.pop();
+    }
+    let content = body.join("\n").trim().to_string();
+    Section {
+        name,
+        content: if content == "(empty)" {
+            String::new()
+        } else {
+            content
+        },
+        confidence: 1.0,
+        detected_by: None,
+        resolved_by: None,
+        part: None,
+        canonical_name: None,
+        raw_content: None,
+        raw_heading,
+        span: None,
+        vitals: None,
+    }
+}
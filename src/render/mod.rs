@@ -1,7 +1,12 @@
 pub mod csv;
+pub mod fhir;
+pub mod html;
 pub mod json;
 pub mod markdown;
+pub mod ndjson;
+pub mod yaml;
 
+use crate::config::Config;
 use crate::models::{CsvLayout, StructuredNote};
 use anyhow::Result;
 use clap::ValueEnum;
@@ -14,6 +19,11 @@ pub enum OutputFormat {
     Md,
     Json,
     Csv,
+    Tsv,
+    Yaml,
+    Html,
+    Ndjson,
+    Fhir,
 }
 
 impl OutputFormat {
@@ -22,6 +32,11 @@ impl OutputFormat {
             OutputFormat::Md => "md",
             OutputFormat::Json => "json",
             OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Html => "html",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Fhir => "fhir.json",
         }
     }
 }
@@ -30,10 +45,28 @@ pub fn render_notes(
     notes: &[StructuredNote],
     format: OutputFormat,
     layout: CsvLayout,
+) -> Result<String> {
+    render_notes_with(notes, format, layout, &Config::default())
+}
+
+/// Like `render_notes`, but honors renderer settings from the config.
+pub fn render_notes_with(
+    notes: &[StructuredNote],
+    format: OutputFormat,
+    layout: CsvLayout,
+    config: &Config,
 ) -> Result<String> {
     match format {
-        OutputFormat::Md => Ok(markdown::render_notes(notes)),
-        OutputFormat::Json => json::render_notes(notes),
-        OutputFormat::Csv => csv::render_notes(notes, layout),
+        OutputFormat::Md => Ok(markdown::render_notes_with_separator(
+            notes,
+            &config.markdown.note_separator,
+        )),
+        OutputFormat::Json => json::render_notes_with(notes, &config.json),
+        OutputFormat::Csv => csv::render_notes_with(notes, layout, &config.csv),
+        OutputFormat::Tsv => csv::render_notes_tsv_with(notes, layout, &config.csv),
+        OutputFormat::Yaml => yaml::render_notes(notes),
+        OutputFormat::Html => html::render_notes(notes),
+        OutputFormat::Ndjson => ndjson::render_notes(notes),
+        OutputFormat::Fhir => fhir::render_notes(notes),
     }
 }
@@ -0,0 +1,12 @@
+use crate::models::StructuredNote;
+use anyhow::Result;
+
+/// One compact JSON object per line, never wrapped in an array.
+pub fn render_notes(notes: &[StructuredNote]) -> Result<String> {
+    let mut out = String::new();
+    for note in notes {
+        out.push_str(&serde_json::to_string(note)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
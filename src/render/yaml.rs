@@ -0,0 +1,10 @@
+use crate::models::StructuredNote;
+use anyhow::Result;
+
+pub fn render_notes(notes: &[StructuredNote]) -> Result<String> {
+    if notes.len() == 1 {
+        Ok(serde_yaml::to_string(&notes[0])?)
+    } else {
+        Ok(serde_yaml::to_string(&notes)?)
+    }
+}
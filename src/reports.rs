@@ -1,4 +1,8 @@
-use crate::models::StructuredNote;
+use crate::config::Config;
+use crate::models::{ParseWarning, Section, StructuredNote};
+use crate::parser::warnings;
+use crate::util;
+use crate::validate::{self, Severity, Template, ValidationIssue};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -10,6 +14,90 @@ pub struct BatchFailure {
     pub error: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileTiming {
+    pub file: String,
+    pub ms: u128,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeStats {
+    pub p50_ms: u128,
+    pub p90_ms: u128,
+    pub p99_ms: u128,
+    pub slowest_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnmappedHeading {
+    pub heading: String,
+    pub count: usize,
+}
+
+/// Everything known about one note: its sections, parser warnings and
+/// validation issues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteReport {
+    pub id: String,
+    pub note_index: usize,
+    pub sections: Vec<Section>,
+    pub warnings: Vec<ParseWarning>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentReport {
+    pub input: String,
+    pub template: Template,
+    pub strict: bool,
+    pub notes: Vec<NoteReport>,
+}
+
+/// Parses and validates `text`, pairing each note's structure with its issues.
+pub fn document_report(
+    text: &str,
+    template: Template,
+    strict: bool,
+    config: &Config,
+    input: &str,
+) -> DocumentReport {
+    let notes = validate::validate_notes(text, template, strict, config, Some(input.to_string()))
+        .into_iter()
+        .map(|(note, issues)| NoteReport {
+            id: note.id,
+            note_index: note.note_index,
+            sections: note.sections,
+            warnings: note.warnings,
+            issues,
+        })
+        .collect();
+    DocumentReport {
+        input: input.to_string(),
+        template,
+        strict,
+        notes,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteIssues {
+    pub note_index: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileValidation {
+    pub file: String,
+    pub notes: Vec<NoteIssues>,
+}
+
+/// Contents of `validation_report.json` from `clinote batch --validate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchValidationReport {
+    pub template: Template,
+    pub files: Vec<FileValidation>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BatchReport {
     pub tool_name: String,
@@ -19,8 +107,22 @@ pub struct BatchReport {
     pub failed_files: usize,
     pub counts_by_section: HashMap<String, usize>,
     pub warnings_count: usize,
+    pub narrative_only_files: usize,
+    pub timed_out_files: usize,
+    pub encodings: HashMap<String, usize>,
     pub failures: Vec<BatchFailure>,
     pub runtime_ms: u128,
+    pub per_file_ms: Vec<FileTiming>,
+    pub runtime_stats: RuntimeStats,
+    pub validation_errors_by_code: HashMap<String, usize>,
+    pub validation_warnings_by_code: HashMap<String, usize>,
+    #[serde(skip)]
+    pub unmapped_headings: HashMap<String, usize>,
+    #[serde(skip)]
+    pub validation: Vec<FileValidation>,
+    /// Notes of every successful file, for `clinote batch --combined`.
+    #[serde(skip)]
+    pub combined: Vec<StructuredNote>,
 }
 
 impl BatchReport {
@@ -33,13 +135,31 @@ impl BatchReport {
             failed_files: 0,
             counts_by_section: HashMap::new(),
             warnings_count: 0,
+            narrative_only_files: 0,
+            timed_out_files: 0,
+            encodings: HashMap::new(),
             failures: Vec::new(),
             runtime_ms: 0,
+            per_file_ms: Vec::new(),
+            runtime_stats: RuntimeStats::default(),
+            validation_errors_by_code: HashMap::new(),
+            validation_warnings_by_code: HashMap::new(),
+            unmapped_headings: HashMap::new(),
+            validation: Vec::new(),
+            combined: Vec::new(),
         }
     }
 
     pub fn record_ok(&mut self, notes: &[StructuredNote]) {
+        self.record_ok_with(notes, "Narrative");
+    }
+
+    /// Like `record_ok`, with `fallback_section` as the narrative section name.
+    pub fn record_ok_with(&mut self, notes: &[StructuredNote], fallback_section: &str) {
         self.ok_files += 1;
+        if Self::narrative_only_with(notes, fallback_section) {
+            self.narrative_only_files += 1;
+        }
         for note in notes {
             for section in &note.sections {
                 *self
@@ -48,9 +168,72 @@ impl BatchReport {
                     .or_insert(0) += 1;
             }
             self.warnings_count += note.warnings.len();
+            for heading in note
+                .warnings
+                .iter()
+                .filter_map(warnings::unmapped_heading_text)
+            {
+                *self
+                    .unmapped_headings
+                    .entry(heading.to_string())
+                    .or_insert(0) += 1;
+            }
         }
     }
 
+    pub fn record_validation(&mut self, validation: FileValidation) {
+        for issue in validation.notes.iter().flat_map(|note| &note.issues) {
+            let counts = match issue.severity {
+                Severity::Error => &mut self.validation_errors_by_code,
+                Severity::Warn => &mut self.validation_warnings_by_code,
+                Severity::Info => continue,
+            };
+            *counts.entry(issue.code.clone()).or_insert(0) += 1;
+        }
+        self.validation.push(validation);
+    }
+
+    /// Unmapped headings seen across the batch, most frequent first.
+    pub fn unmapped_ranking(&self) -> Vec<UnmappedHeading> {
+        let mut ranking: Vec<UnmappedHeading> = self
+            .unmapped_headings
+            .iter()
+            .map(|(heading, count)| UnmappedHeading {
+                heading: heading.clone(),
+                count: *count,
+            })
+            .collect();
+        ranking.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.heading.cmp(&b.heading))
+        });
+        ranking
+    }
+
+    /// True when any note collapsed to Narrative content only. A bundle file
+    /// with one unstructured note among structured ones counts, so
+    /// `--strict-structure` fails the whole file.
+    pub fn narrative_only(notes: &[StructuredNote]) -> bool {
+        Self::narrative_only_with(notes, "Narrative")
+    }
+
+    /// Like `narrative_only`, for a configured `fallback_section_name`.
+    pub fn narrative_only_with(notes: &[StructuredNote], fallback_section: &str) -> bool {
+        let narrative = util::normalize_heading_key(fallback_section);
+        notes.iter().any(|note| {
+            !note.sections.is_empty()
+                && note
+                    .sections
+                    .iter()
+                    .all(|s| util::normalize_heading_key(s.canonical()) == narrative)
+        })
+    }
+
+    pub fn record_encoding(&mut self, encoding: &str) {
+        *self.encodings.entry(encoding.to_string()).or_insert(0) += 1;
+    }
+
     pub fn record_failure(&mut self, file: &str, error: String) {
         self.failed_files += 1;
         self.failures.push(BatchFailure {
@@ -59,13 +242,80 @@ impl BatchReport {
         });
     }
 
+    pub fn record_timing(&mut self, file: &str, ms: u128) {
+        self.per_file_ms.push(FileTiming {
+            file: file.to_string(),
+            ms,
+        });
+    }
+
+    pub fn record_timeout(&mut self, file: &str, timeout_ms: u64) {
+        self.timed_out_files += 1;
+        self.record_failure(file, format!("timeout: exceeded {} ms", timeout_ms));
+    }
+
     pub fn finalize(&mut self) {
         self.total_files = self.ok_files + self.failed_files;
+        let mut timings: Vec<u128> = self.per_file_ms.iter().map(|t| t.ms).collect();
+        timings.sort_unstable();
+        self.runtime_stats = RuntimeStats {
+            p50_ms: percentile(&timings, 50),
+            p90_ms: percentile(&timings, 90),
+            p99_ms: percentile(&timings, 99),
+            slowest_file: self
+                .per_file_ms
+                .iter()
+                .max_by_key(|t| t.ms)
+                .map(|t| t.file.clone()),
+        };
     }
 
     pub fn write_to(&self, path: &Path) -> Result<()> {
         let json = serde_json::to_string_pretty(self)?;
-        crate::util::write_string(path, &json)?;
+        util::write_string(path, &json)?;
+        Ok(())
+    }
+
+    /// Writes the headline totals and runtime percentiles as `metric,value` rows.
+    pub fn write_csv_to(&self, path: &Path) -> Result<()> {
+        util::write_string(path, &self.to_csv()?)?;
         Ok(())
     }
+
+    pub fn to_csv(&self) -> Result<String> {
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        wtr.write_record(["metric", "value"])?;
+        let rows = [
+            ("total_files", self.total_files.to_string()),
+            ("ok_files", self.ok_files.to_string()),
+            ("failed_files", self.failed_files.to_string()),
+            ("warnings_count", self.warnings_count.to_string()),
+            (
+                "narrative_only_files",
+                self.narrative_only_files.to_string(),
+            ),
+            ("timed_out_files", self.timed_out_files.to_string()),
+            ("runtime_ms", self.runtime_ms.to_string()),
+            ("p50_ms", self.runtime_stats.p50_ms.to_string()),
+            ("p90_ms", self.runtime_stats.p90_ms.to_string()),
+            ("p99_ms", self.runtime_stats.p99_ms.to_string()),
+            (
+                "slowest_file",
+                self.runtime_stats.slowest_file.clone().unwrap_or_default(),
+            ),
+        ];
+        for (metric, value) in rows {
+            wtr.write_record([metric, value.as_str()])?;
+        }
+        Ok(String::from_utf8(wtr.into_inner()?)?)
+    }
+}
+
+/// Nearest-rank percentile over already sorted values.
+fn percentile(sorted: &[u128], pct: usize) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
 }
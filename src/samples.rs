@@ -1,4 +1,4 @@
-use crate::models::{Metadata, NoteFormat, Section, StructuredNote};
+use crate::models::{ConfidenceSummary, Metadata, NoteFormat, Section, StructuredNote};
 use crate::util;
 use anyhow::Result;
 use std::path::Path;
@@ -67,6 +67,31 @@ fn synthetic_note(format: NoteFormat, index: usize) -> (String, StructuredNote)
             ("Assessment", vec!["Assessment:", "DX:"]),
             ("Plan", vec!["Plan:", "P:"]),
         ],
+        NoteFormat::Progress => vec![
+            ("Interval History", vec!["Interval History:", "INTERVAL HX"]),
+            ("Events", vec!["Events:", "Overnight Events:"]),
+            ("Subjective", vec!["Subjective:", "S:"]),
+            ("Objective", vec!["Objective:", "O:"]),
+            ("Assessment", vec!["Assessment:", "A:"]),
+            ("Plan", vec!["Plan:", "P:"]),
+        ],
+        NoteFormat::Operative => vec![
+            (
+                "Preoperative Diagnosis",
+                vec!["Preoperative Diagnosis:", "Preop Dx:"],
+            ),
+            (
+                "Postoperative Diagnosis",
+                vec!["Postoperative Diagnosis:", "Postop Dx:"],
+            ),
+            ("Procedure", vec!["Procedure:", "PROCEDURE"]),
+            ("Findings", vec!["Findings:", "Operative Findings:"]),
+            (
+                "Estimated Blood Loss",
+                vec!["Estimated Blood Loss:", "EBL:"],
+            ),
+            ("Specimens", vec!["Specimens:", "Specimen:"]),
+        ],
         NoteFormat::Discharge => vec![
             (
                 "Admission Dx",
@@ -102,9 +127,19 @@ fn synthetic_note(format: NoteFormat, index: usize) -> (String, StructuredNote)
             name: name.to_string(),
             content,
             confidence: 0.95,
+            detected_by: None,
+            resolved_by: None,
+            part: None,
+            canonical_name: None,
+            raw_content: None,
+            raw_heading: None,
+            span: None,
+            vitals: None,
         });
     }
 
+    let content_hash = util::content_hash(&sections);
+    let confidence = ConfidenceSummary::from_sections(&sections);
     let note = StructuredNote {
         id: format!("sample-{}", index),
         format,
@@ -112,6 +147,8 @@ fn synthetic_note(format: NoteFormat, index: usize) -> (String, StructuredNote)
         note_index: index,
         sections,
         warnings: Vec::new(),
+        content_hash,
+        confidence,
         metadata: Metadata {
             generated_at: util::now_iso(),
             tool_version: env!("CARGO_PKG_VERSION").to_string(),
@@ -1,4 +1,6 @@
 use crate::config::Config;
+use crate::ignore::IgnoreList;
+use crate::models::StructuredNote;
 use crate::parser::{self, ParseOptions};
 use crate::render::{self, OutputFormat};
 use crate::validate::{self, Severity, Template, ValidationIssue};
@@ -30,43 +32,90 @@ pub struct SelftestSummary {
     pub top_failing: Vec<FileResult>,
 }
 
+#[derive(Debug, Clone)]
+pub struct SelftestOptions {
+    /// Source file extensions to pick up in directory and glob modes.
+    pub extensions: Vec<String>,
+    /// Only write outputs for files with errors, warnings or runtime errors,
+    /// plus a `<stem>.issues.json` for each of them.
+    pub failures_only: bool,
+}
+
+impl Default for SelftestOptions {
+    fn default() -> Self {
+        Self {
+            extensions: vec!["txt".to_string()],
+            failures_only: false,
+        }
+    }
+}
+
 pub fn run_selftest(
     fixtures: &str,
     template: Template,
     strict: bool,
     out_dir: Option<&Path>,
+) -> Result<SelftestSummary> {
+    run_selftest_with(
+        fixtures,
+        template,
+        strict,
+        out_dir,
+        &SelftestOptions::default(),
+    )
+}
+
+pub fn run_selftest_with(
+    fixtures: &str,
+    template: Template,
+    strict: bool,
+    out_dir: Option<&Path>,
+    options: &SelftestOptions,
 ) -> Result<SelftestSummary> {
     let config = Config::default();
-    let files = collect_files(fixtures)?;
+    let files = collect_files(fixtures, &options.extensions)?;
     let mut results = Vec::new();
 
     for path in files {
-        let result = process_file(&path, template, strict, out_dir, &config);
+        let (result, notes) = process_file(&path, template, strict, &config);
+        if let Some(out_dir) = out_dir {
+            if !options.failures_only {
+                write_outputs(out_dir, &path, &notes, &config);
+            } else if is_failing(&result) {
+                write_outputs(out_dir, &path, &notes, &config);
+                let issues = serde_json::to_string_pretty(&result).unwrap_or_default();
+                let issues_path = out_dir.join(format!("{}.issues.json", util::file_stem(&path)));
+                let _ = util::write_string(&issues_path, &issues);
+            }
+        }
         results.push(result);
     }
 
     Ok(summarize(fixtures, template, strict, results))
 }
 
-fn collect_files(fixtures: &str) -> Result<Vec<PathBuf>> {
+fn collect_files(fixtures: &str, extensions: &[String]) -> Result<Vec<PathBuf>> {
     let path = Path::new(fixtures);
     if path.exists() && path.is_dir() {
         let mut files = Vec::new();
         visit_dir(path, &mut files)?;
-        files.retain(|p| p.extension().and_then(|e| e.to_str()) == Some("txt"));
+        files.retain(|p| has_allowed_extension(p, extensions));
+        IgnoreList::load(path).retain(&mut files);
         files.sort();
         return Ok(files);
     }
 
     if has_glob_meta(fixtures) {
         let mut files = Vec::new();
-        for entry in glob::glob(fixtures)? {
-            match entry {
-                Ok(path) => files.push(path),
-                Err(_) => {}
+        for pattern in util::expand_braces(fixtures) {
+            for path in glob::glob(&pattern)?.flatten() {
+                files.push(path);
             }
         }
+        files.retain(|p| p.is_file() && has_allowed_extension(p, extensions));
+        IgnoreList::load(&glob_base(fixtures)).retain(&mut files);
         files.sort();
+        files.dedup();
         return Ok(files);
     }
 
@@ -77,6 +126,31 @@ fn collect_files(fixtures: &str) -> Result<Vec<PathBuf>> {
     Err(anyhow!("Fixtures path not found: {}", fixtures))
 }
 
+fn has_allowed_extension(path: &Path, extensions: &[String]) -> bool {
+    let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    extensions
+        .iter()
+        .any(|allowed| allowed.trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
+/// The leading directories of a glob pattern, up to the first wildcard.
+fn glob_base(pattern: &str) -> PathBuf {
+    let mut base = PathBuf::new();
+    for component in Path::new(pattern).components() {
+        if has_glob_meta(&component.as_os_str().to_string_lossy()) {
+            break;
+        }
+        base.push(component);
+    }
+    if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    }
+}
+
 fn has_glob_meta(input: &str) -> bool {
     input.contains('*') || input.contains('?') || input.contains('[') || input.contains('{')
 }
@@ -98,9 +172,8 @@ fn process_file(
     path: &Path,
     template: Template,
     strict: bool,
-    out_dir: Option<&Path>,
     config: &Config,
-) -> FileResult {
+) -> (FileResult, Vec<StructuredNote>) {
     match util::read_to_string(path) {
         Ok(content) => {
             let (note_texts, bundle_warnings) =
@@ -115,34 +188,28 @@ fn process_file(
                     config,
                     ParseOptions {
                         apply_heuristics: config.enable_fallback_heuristics,
+                        min_confidence: 0.0,
                     },
                 );
                 warnings.extend(bundle_warnings.clone());
-                let note = parser::build_note(
+                let note = parser::build_note_with(
                     candidates,
                     template_to_format(template),
                     Some(path.display().to_string()),
                     idx + 1,
                     warnings,
+                    &config.id_template,
+                );
+                let issues = validate::validate_note_rules(
+                    &note,
+                    &validate::TemplateRules::builtin_with(template, config),
+                    strict,
+                    &config.validation,
                 );
-                let issues = validate::validate_note(&note, template, strict);
                 all_issues.extend(issues);
                 notes.push(note);
             }
 
-            if let Some(out_dir) = out_dir {
-                let stem = util::file_stem(path);
-                let md = render::render_notes(&notes, OutputFormat::Md, config.csv.layout)
-                    .unwrap_or_else(|_| "".to_string());
-                let json = render::render_notes(&notes, OutputFormat::Json, config.csv.layout)
-                    .unwrap_or_else(|_| "".to_string());
-                let csv = render::render_notes(&notes, OutputFormat::Csv, config.csv.layout)
-                    .unwrap_or_else(|_| "".to_string());
-                let _ = util::write_string(&out_dir.join(format!("{}.md", stem)), &md);
-                let _ = util::write_string(&out_dir.join(format!("{}.json", stem)), &json);
-                let _ = util::write_string(&out_dir.join(format!("{}.csv", stem)), &csv);
-            }
-
             let errors = all_issues
                 .iter()
                 .filter(|i| i.severity == Severity::Error)
@@ -152,24 +219,49 @@ fn process_file(
                 .filter(|i| i.severity == Severity::Warn)
                 .count();
 
-            FileResult {
+            let result = FileResult {
                 file: path.display().to_string(),
                 notes: notes.len(),
                 errors,
                 warnings,
                 issues: all_issues,
                 runtime_error: None,
-            }
+            };
+            (result, notes)
         }
-        Err(err) => FileResult {
-            file: path.display().to_string(),
-            notes: 0,
-            errors: 0,
-            warnings: 0,
-            issues: Vec::new(),
-            runtime_error: Some(err.to_string()),
-        },
+        Err(err) => {
+            let result = FileResult {
+                file: path.display().to_string(),
+                notes: 0,
+                errors: 0,
+                warnings: 0,
+                issues: Vec::new(),
+                runtime_error: Some(err.to_string()),
+            };
+            (result, Vec::new())
+        }
+    }
+}
+
+fn is_failing(result: &FileResult) -> bool {
+    result.errors > 0 || result.warnings > 0 || result.runtime_error.is_some()
+}
+
+fn write_outputs(out_dir: &Path, path: &Path, notes: &[StructuredNote], config: &Config) {
+    if notes.is_empty() {
+        return;
     }
+    let stem = util::file_stem(path);
+    let layout = render::csv::resolve_layout(notes, &config.csv).unwrap_or(config.csv.layout);
+    let md = render::render_notes_with(notes, OutputFormat::Md, layout, config)
+        .unwrap_or_else(|_| "".to_string());
+    let json = render::render_notes_with(notes, OutputFormat::Json, layout, config)
+        .unwrap_or_else(|_| "".to_string());
+    let csv = render::render_notes_with(notes, OutputFormat::Csv, layout, config)
+        .unwrap_or_else(|_| "".to_string());
+    let _ = util::write_string(&out_dir.join(format!("{}.md", stem)), &md);
+    let _ = util::write_string(&out_dir.join(format!("{}.json", stem)), &json);
+    let _ = util::write_string(&out_dir.join(format!("{}.csv", stem)), &csv);
 }
 
 fn summarize(fixtures: &str, template: Template, strict: bool, results: Vec<FileResult>) -> SelftestSummary {
@@ -190,7 +282,13 @@ fn summarize(fixtures: &str, template: Template, strict: bool, results: Vec<File
     }
 
     let mut top = results.clone();
-    top.sort_by_key(|r| (std::cmp::Reverse(r.errors), std::cmp::Reverse(r.warnings)));
+    top.sort_by_key(|r| {
+        (
+            std::cmp::Reverse(r.errors),
+            std::cmp::Reverse(r.warnings),
+            r.file.clone(),
+        )
+    });
     top.truncate(5);
 
     SelftestSummary {
@@ -211,6 +309,8 @@ fn template_to_format(template: Template) -> crate::models::NoteFormat {
         Template::Soap => crate::models::NoteFormat::Soap,
         Template::Hp => crate::models::NoteFormat::Hp,
         Template::Discharge => crate::models::NoteFormat::Discharge,
+        Template::Progress => crate::models::NoteFormat::Progress,
+        Template::Operative => crate::models::NoteFormat::Operative,
     }
 }
 
@@ -226,7 +326,7 @@ pub fn summarize_text(summary: &SelftestSummary) -> String {
     out.push_str(&format!("Runtime failures: {}\n", summary.runtime_failures));
     out.push_str("Top failing files:\n");
     for result in &summary.top_failing {
-        if result.errors > 0 || result.warnings > 0 || result.runtime_error.is_some() {
+        if is_failing(result) {
             let reason = result
                 .runtime_error
                 .clone()
@@ -0,0 +1,111 @@
+use crate::config::Config;
+use crate::models::NoteFormat;
+use crate::parser::{self, ParseOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectionStats {
+    pub name: String,
+    /// Notes containing the section at least once.
+    pub notes: usize,
+    /// `notes` as a fraction of all notes in the corpus.
+    pub presence_rate: f64,
+    /// Mean character count over every occurrence of the section.
+    pub avg_chars: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorpusStats {
+    pub format: NoteFormat,
+    pub files: usize,
+    pub notes: usize,
+    /// Most common sections first, ties broken by name.
+    pub sections: Vec<SectionStats>,
+}
+
+#[derive(Default)]
+struct Tally {
+    notes: usize,
+    occurrences: usize,
+    chars: usize,
+}
+
+/// Splits each file's text into notes and aggregates section presence and size
+/// across all of them.
+pub fn corpus_stats<S: AsRef<str>>(
+    files: &[S],
+    format: NoteFormat,
+    config: &Config,
+) -> CorpusStats {
+    let options = ParseOptions {
+        apply_heuristics: config.enable_fallback_heuristics,
+        min_confidence: 0.0,
+    };
+    let mut tallies: HashMap<String, Tally> = HashMap::new();
+    let mut notes = 0;
+    for text in files {
+        let (note_texts, _) =
+            parser::split_bundle(text.as_ref(), config.bundle.mode_default, config);
+        for note_text in note_texts {
+            notes += 1;
+            let (candidates, _) = parser::extract_candidates(&note_text, format, config, options);
+            let mut seen = HashSet::new();
+            for candidate in candidates {
+                let tally = tallies.entry(candidate.name.clone()).or_default();
+                tally.occurrences += 1;
+                tally.chars += candidate.content.trim().chars().count();
+                if seen.insert(candidate.name) {
+                    tally.notes += 1;
+                }
+            }
+        }
+    }
+
+    let mut sections: Vec<SectionStats> = tallies
+        .into_iter()
+        .map(|(name, tally)| SectionStats {
+            name,
+            notes: tally.notes,
+            presence_rate: tally.notes as f64 / notes.max(1) as f64,
+            avg_chars: tally.chars as f64 / tally.occurrences.max(1) as f64,
+        })
+        .collect();
+    sections.sort_by(|a, b| b.notes.cmp(&a.notes).then_with(|| a.name.cmp(&b.name)));
+    CorpusStats {
+        format,
+        files: files.len(),
+        notes,
+        sections,
+    }
+}
+
+pub fn format_table(stats: &CorpusStats) -> String {
+    let width = stats
+        .sections
+        .iter()
+        .map(|s| s.name.len())
+        .chain(std::iter::once("Section".len()))
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "{} notes in {} files ({})\n",
+        stats.notes,
+        stats.files,
+        stats.format.as_str()
+    );
+    out.push_str(&format!(
+        "{:<width$}  {:>6}  {:>8}  {:>9}\n",
+        "Section", "Notes", "Presence", "Avg chars"
+    ));
+    for section in &stats.sections {
+        out.push_str(&format!(
+            "{:<width$}  {:>6}  {:>7.1}%  {:>9.1}\n",
+            section.name,
+            section.notes,
+            section.presence_rate * 100.0,
+            section.avg_chars
+        ));
+    }
+    out
+}
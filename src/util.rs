@@ -1,35 +1,199 @@
-use anyhow::Result;
+use crate::models::{HeadingMatching, Section};
+use anyhow::{anyhow, Result};
 use chrono::Utc;
+use flate2::read::GzDecoder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+type PatternCache = HashMap<Vec<String>, Arc<Vec<Regex>>>;
+
+static PATTERN_CACHE: Lazy<Mutex<PatternCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Config regexes, compiled once per distinct list and reused for the rest of
+/// the run. Invalid entries are skipped; `clinote validate --config` reports
+/// them.
+pub fn compiled_patterns(patterns: &[String]) -> Arc<Vec<Regex>> {
+    let mut cache = PATTERN_CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    cache
+        .entry(patterns.to_vec())
+        .or_insert_with(|| {
+            Arc::new(
+                patterns
+                    .iter()
+                    .filter_map(|pattern| Regex::new(pattern).ok())
+                    .collect(),
+            )
+        })
+        .clone()
+}
 
 pub fn normalize_heading_key(input: &str) -> String {
+    normalize_heading_key_with(input, HeadingMatching::Ascii)
+}
+
+pub fn normalize_heading_key_with(input: &str, matching: HeadingMatching) -> String {
     let mut cleaned = input.trim().trim_end_matches(':').to_string();
     cleaned = cleaned.replace('-', " ");
     cleaned = cleaned.replace('/', " ");
     cleaned = cleaned.replace('&', " ");
+    if matching == HeadingMatching::UnicodeNoAccents {
+        cleaned = cleaned.nfd().filter(|c| !is_combining_mark(*c)).collect();
+    }
     let mut out = String::new();
     let mut last_space = false;
     for ch in cleaned.chars() {
-        if ch.is_ascii_alphanumeric() {
-            out.push(ch.to_ascii_uppercase());
+        let keep = match matching {
+            HeadingMatching::Ascii => ch.is_ascii_alphanumeric(),
+            _ => ch.is_alphanumeric(),
+        };
+        if keep {
+            out.extend(ch.to_uppercase());
             last_space = false;
-        } else if ch.is_whitespace() {
-            if !last_space {
-                out.push(' ');
-                last_space = true;
-            }
+        } else if ch.is_whitespace() && !last_space {
+            out.push(' ');
+            last_space = true;
         }
     }
     out.trim().to_string()
 }
 
+/// Drops a single trailing `(...)` group, e.g. `Assessment (and Plan)` -> `Assessment`.
+pub fn strip_trailing_parenthetical(input: &str) -> &str {
+    let trimmed = input.trim().trim_end_matches(':').trim_end();
+    if let Some(body) = trimmed.strip_suffix(')') {
+        if let Some(open) = body.rfind('(') {
+            if !body[open + 1..].contains(')') {
+                let head = body[..open].trim_end();
+                if !head.is_empty() {
+                    return head;
+                }
+            }
+        }
+    }
+    trimmed
+}
+
+pub fn content_hash(sections: &[Section]) -> String {
+    let mut hasher = Sha256::new();
+    for section in sections {
+        hasher.update(section.name.as_bytes());
+        hasher.update([0x1f]);
+        hasher.update(section.content.as_bytes());
+        hasher.update([0x1e]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 pub fn now_iso() -> String {
     Utc::now().to_rfc3339()
 }
 
+/// Reads a file as UTF-8, decompressing it first when it ends in `.gz`, and
+/// drops a leading byte order mark so bundle splitting and date detection see
+/// the first line as written. `normalize_text` drops it too, for text handed to
+/// the parser without going through these readers.
 pub fn read_to_string(path: &Path) -> Result<String> {
-    Ok(fs::read_to_string(path)?)
+    if is_gzip(path) {
+        decode_text(&read_bytes(path)?)
+    } else {
+        Ok(strip_bom(fs::read_to_string(path)?))
+    }
+}
+
+fn strip_bom(text: String) -> String {
+    match text.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => text,
+    }
+}
+
+pub fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"))
+}
+
+/// True for the `-` path that stands for stdin or stdout.
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Like `read_to_string`, reading stdin when `path` is `-`.
+pub fn read_input(path: &Path) -> Result<String> {
+    read_input_with(path, None)
+}
+
+/// Like `read_input`, decoding with `encoding` when one is given.
+pub fn read_input_with(path: &Path, encoding: Option<&str>) -> Result<String> {
+    if is_stdio(path) {
+        let mut input = Vec::new();
+        std::io::stdin().read_to_end(&mut input)?;
+        decode_text_with(&input, encoding)
+    } else {
+        read_to_string_with(path, encoding)
+    }
+}
+
+pub fn read_bytes(path: &Path) -> Result<Vec<u8>> {
+    if is_gzip(path) {
+        let mut bytes = Vec::new();
+        GzDecoder::new(fs::File::open(path)?).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        Ok(fs::read(path)?)
+    }
+}
+
+pub fn decode_text(bytes: &[u8]) -> Result<String> {
+    Ok(strip_bom(std::str::from_utf8(bytes)?.to_string()))
+}
+
+/// Like `decode_text`, decoding with the named encoding (any WHATWG label such
+/// as `windows-1252` or `latin1`) when one is given. Malformed sequences
+/// become U+FFFD instead of failing. A byte order mark is dropped either way.
+pub fn decode_text_with(bytes: &[u8], encoding: Option<&str>) -> Result<String> {
+    let Some(label) = encoding else {
+        return decode_text(bytes);
+    };
+    let encoding = encoding_rs::Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| anyhow!("Unknown encoding '{}'", label))?;
+    let (text, _, _) = encoding.decode(bytes);
+    Ok(text.into_owned())
+}
+
+/// Like `read_to_string`, decoding with `encoding` when one is given.
+pub fn read_to_string_with(path: &Path, encoding: Option<&str>) -> Result<String> {
+    match encoding {
+        Some(_) => decode_text_with(&read_bytes(path)?, encoding),
+        None => read_to_string(path),
+    }
+}
+
+/// Best-effort label for the byte encoding of an input file.
+pub fn detect_encoding(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        "utf-8-bom"
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        "utf-16le"
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        "utf-16be"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "utf-8"
+    } else {
+        "unknown"
+    }
 }
 
 pub fn write_string(path: &Path, content: &str) -> Result<()> {
@@ -40,9 +204,111 @@ pub fn write_string(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// `write_output`, or for `--dry-run` just the intended path and size on stderr.
+pub fn write_output_unless_dry_run(path: &Path, content: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        eprintln!(
+            "dry run: would write {} ({} bytes)",
+            path.display(),
+            content.len()
+        );
+        Ok(())
+    } else {
+        write_output(path, content)
+    }
+}
+
+/// Like `write_string`, writing to stdout when `path` is `-`.
+pub fn write_output(path: &Path, content: &str) -> Result<()> {
+    if is_stdio(path) {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(content.as_bytes())?;
+        if !content.ends_with('\n') {
+            stdout.write_all(b"\n")?;
+        }
+        stdout.flush()?;
+        Ok(())
+    } else {
+        write_string(path, content)
+    }
+}
+
+pub const DEFAULT_OUT_TEMPLATE: &str = "{stem}.{ext}";
+
+/// Expands the `{stem}`, `{format}`, `{index}` and `{ext}` placeholders of a
+/// batch `--out-template`.
+pub fn expand_out_template(
+    template: &str,
+    stem: &str,
+    format: &str,
+    index: usize,
+    ext: &str,
+) -> String {
+    template
+        .replace("{stem}", stem)
+        .replace("{format}", format)
+        .replace("{index}", &index.to_string())
+        .replace("{ext}", ext)
+}
+
+pub const DEFAULT_ID_TEMPLATE: &str = "note-{index}-{timestamp}";
+
+/// Expands the `{index}`, `{stem}`, `{timestamp}` and `{format}` placeholders
+/// of a note `id_template`. `{stem}` is the source file stem, or `note` when
+/// there is no source file.
+pub fn expand_id_template(
+    template: &str,
+    index: usize,
+    source_file: Option<&str>,
+    format: &str,
+) -> String {
+    let stem = source_file
+        .map(|source| file_stem(Path::new(source)))
+        .unwrap_or_else(|| "note".to_string());
+    let mut id = template
+        .replace("{index}", &index.to_string())
+        .replace("{stem}", &stem)
+        .replace("{format}", format);
+    if id.contains("{timestamp}") {
+        id = id.replace("{timestamp}", &now_iso());
+    }
+    id
+}
+
+/// The file name without its extension, or without both for `.gz` files
+/// (`note.txt.gz` gives `note`).
 pub fn file_stem(path: &Path) -> String {
+    let path = if is_gzip(path) {
+        Path::new(path.file_stem().unwrap_or_default())
+    } else {
+        path
+    };
     path.file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("output")
         .to_string()
 }
+
+/// Expands `{a,b}` alternations, which the `glob` crate doesn't support, into
+/// one pattern per alternative (`*.{txt,note}` gives `*.txt` and `*.note`).
+/// Several groups multiply out; nested braces are left as written.
+pub fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+    let Some(close) = pattern[open..].find('}').map(|idx| open + idx) else {
+        return vec![pattern.to_string()];
+    };
+    let alternatives = &pattern[open + 1..close];
+    if alternatives.contains('{') {
+        return vec![pattern.to_string()];
+    }
+    let (prefix, rest) = (&pattern[..open], &pattern[close + 1..]);
+    let mut expanded = Vec::new();
+    for tail in expand_braces(rest) {
+        for alternative in alternatives.split(',') {
+            expanded.push(format!("{}{}{}", prefix, alternative, tail));
+        }
+    }
+    expanded
+}
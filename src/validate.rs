@@ -1,21 +1,14 @@
-use crate::models::StructuredNote;
+use crate::config::{Config, ContentRule, CustomTemplate, ValidationConfig};
+use crate::models::{NoteFormat, StructuredNote};
+use crate::parser::{self, headings, normalize, ParseOptions};
 use crate::util;
+use chrono::{Datelike, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-pub enum Severity {
-    Info,
-    Warn,
-    Error,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Span {
-    pub line_start: usize,
-    pub line_end: usize,
-}
+pub use crate::models::{Severity, Span};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationIssue {
@@ -33,6 +26,119 @@ pub enum Template {
     Soap,
     Hp,
     Discharge,
+    Progress,
+    Operative,
+}
+
+impl Template {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Template::Soap => "soap",
+            Template::Hp => "hp",
+            Template::Discharge => "discharge",
+            Template::Progress => "progress",
+            Template::Operative => "operative",
+        }
+    }
+
+    pub fn from_format(format: NoteFormat) -> Self {
+        match format {
+            NoteFormat::Soap => Template::Soap,
+            NoteFormat::Hp => Template::Hp,
+            NoteFormat::Discharge => Template::Discharge,
+            NoteFormat::Progress => Template::Progress,
+            NoteFormat::Operative => Template::Operative,
+        }
+    }
+
+    pub fn note_format(&self) -> NoteFormat {
+        match self {
+            Template::Soap => NoteFormat::Soap,
+            Template::Hp => NoteFormat::Hp,
+            Template::Discharge => NoteFormat::Discharge,
+            Template::Progress => NoteFormat::Progress,
+            Template::Operative => NoteFormat::Operative,
+        }
+    }
+}
+
+/// What a template checks, resolved from a built-in `Template` or a
+/// `[[templates]]` config entry.
+#[derive(Debug, Clone)]
+pub struct TemplateRules {
+    pub name: String,
+    /// Format the note is parsed with.
+    pub format: NoteFormat,
+    /// Each group is satisfied by any one of its section names.
+    pub required: Vec<Vec<String>>,
+    pub optional: Vec<String>,
+    /// Expected section order for `out_of_order`; names in one group share a
+    /// position.
+    pub order: Vec<Vec<String>>,
+}
+
+impl TemplateRules {
+    /// Like `builtin_with`, ordered by the default `section_order`.
+    pub fn builtin(template: Template) -> Self {
+        Self::builtin_with(template, &Config::default())
+    }
+
+    /// A built-in template, ordered by `config`'s `section_order` for its
+    /// format.
+    pub fn builtin_with(template: Template, config: &Config) -> Self {
+        Self {
+            name: template.as_str().to_string(),
+            format: template.note_format(),
+            required: required_groups(template),
+            optional: optional_sections(template)
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect(),
+            order: config
+                .section_order(template.note_format())
+                .into_iter()
+                .map(|name| vec![name])
+                .collect(),
+        }
+    }
+
+    /// Custom templates are ordered by their `required_sections`.
+    pub fn custom(template: &CustomTemplate) -> Self {
+        Self {
+            name: template.name.clone(),
+            format: template.format,
+            required: template.required_sections.clone(),
+            optional: template.optional_sections.clone(),
+            order: template.required_sections.clone(),
+        }
+    }
+
+    /// Adds the configured `fallback_section_name` as an optional section, so
+    /// heading-less notes aren't flagged unknown under a custom name.
+    pub fn with_fallback_section(&self, name: &str) -> Self {
+        let mut rules = self.clone();
+        let key = util::normalize_heading_key(name);
+        if !rules.known_sections().contains(&key) {
+            rules.optional.push(name.to_string());
+        }
+        rules
+    }
+
+    fn known_sections(&self) -> HashSet<String> {
+        self.required
+            .iter()
+            .flatten()
+            .chain(&self.optional)
+            .map(|name| util::normalize_heading_key(name))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSpec {
+    pub name: String,
+    pub required: Vec<Vec<String>>,
+    pub optional: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,23 +146,72 @@ pub struct SectionSummary {
     pub name: String,
     pub line_count: usize,
     pub char_count: usize,
+    pub word_count: usize,
+    /// Flesch-Kincaid grade level; 0.0 for an empty section.
+    pub flesch_kincaid_grade: f32,
 }
 
-const MIN_SECTION_LEN: usize = 20;
+const MAX_AGE_YEARS: i32 = 130;
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%m/%d/%Y",
+    "%m-%d-%Y",
+    "%d.%m.%Y",
+    "%B %d, %Y",
+    "%b %d, %Y",
+];
+
+static HEADER_FIELD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<label>[A-Za-z][A-Za-z /.]{1,30}):\s*(?P<value>\S.*)$").unwrap());
 
 pub fn validate_note(
     note: &StructuredNote,
     template: Template,
     strict: bool,
+) -> Vec<ValidationIssue> {
+    validate_note_with(note, template, strict, &ValidationConfig::default())
+}
+
+/// Like `validate_note`, but honors the `[validation]` config settings.
+pub fn validate_note_with(
+    note: &StructuredNote,
+    template: Template,
+    strict: bool,
+    options: &ValidationConfig,
+) -> Vec<ValidationIssue> {
+    validate_note_rules(note, &TemplateRules::builtin(template), strict, options)
+}
+
+/// Like `validate_note_with`, for any resolved template, including a custom
+/// one from `[[templates]]`.
+pub fn validate_note_rules(
+    note: &StructuredNote,
+    rules: &TemplateRules,
+    strict: bool,
+    options: &ValidationConfig,
 ) -> Vec<ValidationIssue> {
     let mut issues = Vec::new();
-    let groups = required_groups(template);
-    let known = known_sections(template);
+    let groups = rules.required.clone();
+    let known = rules.known_sections();
+
+    let required: HashSet<String> = groups
+        .iter()
+        .flatten()
+        .map(|name| util::normalize_heading_key(name))
+        .collect();
+    let min_words: HashMap<String, usize> = options
+        .min_words_by_section
+        .iter()
+        .map(|(name, min)| (util::normalize_heading_key(name), *min))
+        .collect();
 
     let mut counts: HashMap<String, usize> = HashMap::new();
     for section in &note.sections {
-        let key = util::normalize_heading_key(&section.name);
-        *counts.entry(key).or_insert(0) += 1;
+        let key = util::normalize_heading_key(section.canonical());
+        // Numbered parts of one chunked section count once.
+        if section.part.unwrap_or(1) == 1 {
+            *counts.entry(key).or_insert(0) += 1;
+        }
     }
 
     for group in groups {
@@ -87,8 +242,8 @@ pub fn validate_note(
         }
     }
 
-    for section in &note.sections {
-        let key = util::normalize_heading_key(&section.name);
+    for (idx, section) in note.sections.iter().enumerate() {
+        let key = util::normalize_heading_key(section.canonical());
         if counts.get(&key).copied().unwrap_or(0) > 1 {
             issues.push(ValidationIssue {
                 code: "duplicate_section".to_string(),
@@ -97,20 +252,52 @@ pub fn validate_note(
                 section: Some(section.name.clone()),
                 span: None,
             });
+
+            let content = normalize_content(&section.content);
+            let repeated = note.sections[..idx].iter().any(|earlier| {
+                util::normalize_heading_key(earlier.canonical()) == key
+                    && normalize_content(&earlier.content) == content
+            });
+            if repeated {
+                let severity = if strict {
+                    Severity::Error
+                } else {
+                    Severity::Warn
+                };
+                issues.push(ValidationIssue {
+                    code: "duplicate_section_identical".to_string(),
+                    message: format!(
+                        "Duplicate section '{}' repeats identical content",
+                        section.name
+                    ),
+                    severity,
+                    section: Some(section.name.clone()),
+                    span: None,
+                });
+            }
         }
 
         if !known.contains(&key) {
+            let severity = if !options.forbid_unknown_sections {
+                Severity::Info
+            } else if strict {
+                Severity::Error
+            } else {
+                Severity::Warn
+            };
             issues.push(ValidationIssue {
                 code: "unknown_section".to_string(),
                 message: format!("Unknown section '{}'", section.name),
-                severity: Severity::Info,
+                severity,
                 section: Some(section.name.clone()),
                 span: None,
             });
         }
 
         let trimmed = section.content.trim();
-        if trimmed.is_empty() || trimmed.len() < MIN_SECTION_LEN {
+        if options.min_section_len > 0
+            && (trimmed.is_empty() || trimmed.len() < options.min_section_len)
+        {
             issues.push(ValidationIssue {
                 code: "section_too_short".to_string(),
                 message: format!("Section '{}' is empty or too short", section.name),
@@ -119,11 +306,448 @@ pub fn validate_note(
                 span: None,
             });
         }
+
+        if required.contains(&key) && is_placeholder(trimmed, &options.placeholder_phrases) {
+            issues.push(ValidationIssue {
+                code: "placeholder_content".to_string(),
+                message: format!(
+                    "Section '{}' only contains placeholder text '{}'",
+                    section.name, trimmed
+                ),
+                severity: Severity::Warn,
+                section: Some(section.name.clone()),
+                span: None,
+            });
+        }
+
+        if required.contains(&key) && section.confidence < options.confidence_warn {
+            issues.push(ValidationIssue {
+                code: "low_confidence_section".to_string(),
+                message: format!(
+                    "Section '{}' was detected with low confidence {:.2} (below {:.2})",
+                    section.name, section.confidence, options.confidence_warn
+                ),
+                severity: if strict {
+                    Severity::Warn
+                } else {
+                    Severity::Info
+                },
+                section: Some(section.name.clone()),
+                span: None,
+            });
+        }
+
+        if let Some(&min) = min_words.get(&key) {
+            let words = section.content.split_whitespace().count();
+            if words < min {
+                issues.push(ValidationIssue {
+                    code: "section_too_few_words".to_string(),
+                    message: format!(
+                        "Section '{}' has {} words; at least {} required",
+                        section.name, words, min
+                    ),
+                    severity: Severity::Warn,
+                    section: Some(section.name.clone()),
+                    span: None,
+                });
+            }
+        }
     }
 
+    issues.extend(validate_section_order(note, rules, strict));
+    issues.extend(validate_content_rules(note, &options.rules));
     issues
 }
 
+/// Applies `[[validation.rules]]` to every section with the rule's name.
+/// Rules with an invalid regex are skipped; `validate_config` reports them.
+fn validate_content_rules(note: &StructuredNote, rules: &[ContentRule]) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for rule in rules {
+        // `Config::load` rejects invalid patterns; `validate_config` reports
+        // them for configs built in code.
+        let Ok(re) = Regex::new(&rule.pattern) else {
+            continue;
+        };
+        let key = util::normalize_heading_key(&rule.section);
+        for section in &note.sections {
+            if util::normalize_heading_key(section.canonical()) != key {
+                continue;
+            }
+            if re.is_match(&section.content) != rule.must_match {
+                issues.push(ValidationIssue {
+                    code: rule.code.clone(),
+                    message: rule.message.clone(),
+                    severity: rule.severity,
+                    section: Some(section.name.clone()),
+                    span: section.span,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Flags sections that come before one that the template orders ahead of
+/// them. Sections outside the template's order (Narrative, unknown) are ignored.
+fn validate_section_order(
+    note: &StructuredNote,
+    rules: &TemplateRules,
+    strict: bool,
+) -> Vec<ValidationIssue> {
+    let rank: HashMap<String, usize> = rules
+        .order
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, group)| {
+            group
+                .iter()
+                .map(move |name| (util::normalize_heading_key(name), idx))
+        })
+        .collect();
+    let severity = if strict {
+        Severity::Warn
+    } else {
+        Severity::Info
+    };
+    let mut issues = Vec::new();
+    let mut latest: Option<(usize, &str)> = None;
+    for section in &note.sections {
+        let Some(&position) = rank.get(&util::normalize_heading_key(section.canonical())) else {
+            continue;
+        };
+        match latest {
+            Some((latest_position, latest_name)) if position < latest_position => {
+                issues.push(ValidationIssue {
+                    code: "out_of_order".to_string(),
+                    message: format!(
+                        "Section '{}' appears after '{}' but should come before it",
+                        section.name, latest_name
+                    ),
+                    severity,
+                    section: Some(section.name.clone()),
+                    span: None,
+                });
+            }
+            _ => latest = Some((position, &section.name)),
+        }
+    }
+    issues
+}
+
+fn is_placeholder(content: &str, phrases: &[String]) -> bool {
+    !content.is_empty()
+        && phrases
+            .iter()
+            .any(|phrase| phrase.trim().to_lowercase() == content.to_lowercase())
+}
+
+/// Cross-checks each format's `section_order` against the sections its template
+/// (built-in or `[[templates]]`) uses, so parse and validate don't silently
+/// disagree.
+pub fn validate_config(config: &Config) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for (template, format) in [
+        (Template::Soap, NoteFormat::Soap),
+        (Template::Hp, NoteFormat::Hp),
+        (Template::Discharge, NoteFormat::Discharge),
+        (Template::Progress, NoteFormat::Progress),
+        (Template::Operative, NoteFormat::Operative),
+    ] {
+        let order: HashSet<String> = config
+            .section_order(format)
+            .iter()
+            .map(|name| util::normalize_heading_key(name))
+            .collect();
+        for group in required_groups(template) {
+            let configured = group
+                .iter()
+                .any(|alias| order.contains(&util::normalize_heading_key(alias)));
+            if !configured {
+                let name = group.first().cloned().unwrap_or_default();
+                issues.push(ValidationIssue {
+                    code: "section_order_missing_required".to_string(),
+                    message: format!(
+                        "formats.{}.section_order omits '{}', which the {} template requires",
+                        template.as_str(),
+                        name,
+                        template.as_str()
+                    ),
+                    severity: Severity::Warn,
+                    section: Some(name),
+                    span: None,
+                });
+            }
+        }
+    }
+    for template in &config.templates {
+        let order: HashSet<String> = config
+            .section_order(template.format)
+            .iter()
+            .map(|name| util::normalize_heading_key(name))
+            .collect();
+        let missing = template
+            .required_sections
+            .iter()
+            .filter(|group| {
+                !group
+                    .iter()
+                    .any(|name| order.contains(&util::normalize_heading_key(name)))
+            })
+            .filter_map(|group| group.first())
+            .chain(
+                template
+                    .optional_sections
+                    .iter()
+                    .filter(|name| !order.contains(&util::normalize_heading_key(name))),
+            );
+        for name in missing {
+            issues.push(ValidationIssue {
+                code: "template_section_not_in_order".to_string(),
+                message: format!(
+                    "Template '{}' uses '{}', which formats.{}.section_order omits, so it is never detected",
+                    template.name,
+                    name,
+                    template.format.as_str()
+                ),
+                severity: Severity::Warn,
+                section: Some(name.clone()),
+                span: None,
+            });
+        }
+    }
+    for pattern in &config.validation.date_patterns {
+        if let Err(err) = Regex::new(pattern) {
+            issues.push(ValidationIssue {
+                code: "invalid_date_pattern".to_string(),
+                message: format!(
+                    "validation.date_patterns entry '{}' is not a valid regex: {}",
+                    pattern, err
+                ),
+                severity: Severity::Warn,
+                section: None,
+                span: None,
+            });
+        }
+    }
+    for pattern in &config.bundle.date_patterns {
+        if let Err(err) = Regex::new(pattern) {
+            issues.push(ValidationIssue {
+                code: "invalid_bundle_date_pattern".to_string(),
+                message: format!(
+                    "bundle.date_patterns entry '{}' is not a valid regex: {}",
+                    pattern, err
+                ),
+                severity: Severity::Warn,
+                section: None,
+                span: None,
+            });
+        }
+    }
+    for rule in &config.validation.rules {
+        if let Err(err) = Regex::new(&rule.pattern) {
+            issues.push(ValidationIssue {
+                code: "invalid_rule_pattern".to_string(),
+                message: format!(
+                    "validation.rules entry '{}' has an invalid pattern '{}': {}",
+                    rule.code, rule.pattern, err
+                ),
+                severity: Severity::Warn,
+                section: Some(rule.section.clone()),
+                span: None,
+            });
+        }
+    }
+    issues
+}
+
+/// Splits and parses raw text into notes the way `clinote validate` does.
+pub fn notes_for_template(
+    text: &str,
+    template: Template,
+    config: &Config,
+    source_file: Option<String>,
+) -> Vec<StructuredNote> {
+    parser::parse_notes(
+        text,
+        template.note_format(),
+        config,
+        source_file,
+        0,
+        ParseOptions {
+            apply_heuristics: config.enable_fallback_heuristics,
+            min_confidence: 0.0,
+        },
+    )
+}
+
+/// Validates every note in a raw document, keyed by note index.
+pub fn validate_document(
+    text: &str,
+    template: Template,
+    strict: bool,
+    config: &Config,
+) -> Vec<(usize, Vec<ValidationIssue>)> {
+    validate_notes(text, template, strict, config, None)
+        .into_iter()
+        .map(|(note, issues)| (note.note_index, issues))
+        .collect()
+}
+
+/// Parses and validates each note in `text` with `validate_parsed_note`.
+pub fn validate_notes(
+    text: &str,
+    template: Template,
+    strict: bool,
+    config: &Config,
+    source_file: Option<String>,
+) -> Vec<(StructuredNote, Vec<ValidationIssue>)> {
+    validate_notes_rules(
+        text,
+        &TemplateRules::builtin_with(template, config),
+        strict,
+        config,
+        source_file,
+    )
+}
+
+/// Like `validate_notes`, for any resolved template.
+pub fn validate_notes_rules(
+    text: &str,
+    rules: &TemplateRules,
+    strict: bool,
+    config: &Config,
+    source_file: Option<String>,
+) -> Vec<(StructuredNote, Vec<ValidationIssue>)> {
+    let (note_texts, _) = parser::split_bundle(text, config.bundle.mode_default, config);
+    let notes = parser::parse_notes(
+        text,
+        rules.format,
+        config,
+        source_file,
+        0,
+        ParseOptions {
+            apply_heuristics: config.enable_fallback_heuristics,
+            min_confidence: 0.0,
+        },
+    );
+    notes
+        .into_iter()
+        .zip(note_texts)
+        .map(|(note, note_text)| {
+            let issues = validate_parsed_note_rules(&note, &note_text, rules, strict, config);
+            (note, issues)
+        })
+        .collect()
+}
+
+/// Runs every rule on an already parsed note: the structural checks plus the
+/// header date and encounter marker checks that need the note's raw text.
+pub fn validate_parsed_note(
+    note: &StructuredNote,
+    note_text: &str,
+    template: Template,
+    strict: bool,
+    config: &Config,
+) -> Vec<ValidationIssue> {
+    validate_parsed_note_rules(
+        note,
+        note_text,
+        &TemplateRules::builtin_with(template, config),
+        strict,
+        config,
+    )
+}
+
+/// Like `validate_parsed_note`, for any resolved template.
+pub fn validate_parsed_note_rules(
+    note: &StructuredNote,
+    note_text: &str,
+    rules: &TemplateRules,
+    strict: bool,
+    config: &Config,
+) -> Vec<ValidationIssue> {
+    let rules = rules.with_fallback_section(&config.fallback_section_name);
+    let mut issues = validate_note_rules(note, &rules, strict, &config.validation);
+    issues.extend(validate_dates(note_text, config));
+    issues.extend(validate_encounter_date(note_text, config));
+    issues
+}
+
+pub fn validate_dates(text: &str, config: &Config) -> Vec<ValidationIssue> {
+    validate_dates_on(text, config, Local::now().date_naive())
+}
+
+/// Checks `DOB` and other date-like fields in the header (the lines before the
+/// first heading) parse as real dates that are neither in the future nor more
+/// than `MAX_AGE_YEARS` in the past.
+pub fn validate_dates_on(text: &str, config: &Config, today: NaiveDate) -> Vec<ValidationIssue> {
+    let normalized = normalize::normalize_text(text);
+    let lines: Vec<&str> = normalized.lines().collect();
+    let demographics = headings::demographics_block_len(&lines, config);
+    let mut issues = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if idx >= demographics && headings::detect_heading(line, config).is_some() {
+            break;
+        }
+        let Some(caps) = HEADER_FIELD_RE.captures(line.trim()) else {
+            continue;
+        };
+        let label = caps["label"].trim();
+        if !is_date_label(label) {
+            continue;
+        }
+        let value = caps["value"].trim();
+        let problem = match parse_date(value) {
+            None => Some("is not a recognizable date"),
+            Some(date) if date > today => Some("is in the future"),
+            Some(date) if date.year() < today.year() - MAX_AGE_YEARS => Some("is implausibly old"),
+            Some(_) => None,
+        };
+        if let Some(problem) = problem {
+            issues.push(ValidationIssue {
+                code: "invalid_date".to_string(),
+                message: format!("{} '{}' {}", label, value, problem),
+                severity: Severity::Warn,
+                section: None,
+                span: Some(Span {
+                    line_start: idx + 1,
+                    line_end: idx + 1,
+                }),
+            });
+        }
+    }
+    issues
+}
+
+/// Flags notes with no date or encounter marker matching
+/// `[validation] date_patterns`; these are often fragments left by an
+/// over-eager bundle splitter rather than real encounters.
+pub fn validate_encounter_date(text: &str, config: &Config) -> Vec<ValidationIssue> {
+    let patterns = util::compiled_patterns(&config.validation.date_patterns);
+    if patterns.is_empty() || patterns.iter().any(|re| re.is_match(text)) {
+        return Vec::new();
+    }
+    vec![ValidationIssue {
+        code: "no_encounter_date".to_string(),
+        message: "No date or encounter marker found; note may be a split fragment".to_string(),
+        severity: Severity::Info,
+        section: None,
+        span: None,
+    }]
+}
+
+fn is_date_label(label: &str) -> bool {
+    let key = util::normalize_heading_key(label);
+    key == "DOB" || key == "DATE OF BIRTH" || key.split_whitespace().any(|word| word == "DATE")
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
 pub fn summarize_sections(note: &StructuredNote) -> Vec<SectionSummary> {
     note.sections
         .iter()
@@ -133,14 +757,86 @@ pub fn summarize_sections(note: &StructuredNote) -> Vec<SectionSummary> {
                 name: section.name.clone(),
                 line_count,
                 char_count: section.content.chars().count(),
+                word_count: section.content.split_whitespace().count(),
+                flesch_kincaid_grade: flesch_kincaid_grade(&section.content),
             }
         })
         .collect()
 }
 
+/// Sentences are approximated by `.`, `?` and `!` runs, syllables by groups of
+/// vowels in each word.
+pub fn flesch_kincaid_grade(text: &str) -> f32 {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let sentences = text
+        .split(['.', '?', '!'])
+        .filter(|sentence| sentence.split_whitespace().next().is_some())
+        .count()
+        .max(1);
+    let syllables: usize = words.iter().map(|word| syllable_count(word)).sum();
+    let words_per_sentence = words.len() as f32 / sentences as f32;
+    let syllables_per_word = syllables as f32 / words.len() as f32;
+    0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59
+}
+
+fn syllable_count(word: &str) -> usize {
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let is_vowel = |c: &char| "aeiouy".contains(*c);
+    let mut groups = 0;
+    let mut previous_vowel = false;
+    for c in &letters {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            groups += 1;
+        }
+        previous_vowel = vowel;
+    }
+    // A trailing silent `e`, as in "rate".
+    if groups > 1 && letters.ends_with(&['e']) && !letters.ends_with(&['l', 'e']) {
+        groups -= 1;
+    }
+    groups.max(1)
+}
+
+pub fn template_specs() -> Vec<TemplateSpec> {
+    [
+        Template::Soap,
+        Template::Hp,
+        Template::Discharge,
+        Template::Progress,
+        Template::Operative,
+    ]
+    .into_iter()
+    .map(|template| TemplateSpec {
+        name: template.as_str().to_string(),
+        required: required_groups(template),
+        optional: optional_sections(template)
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect(),
+    })
+    .collect()
+}
+
+fn normalize_content(content: &str) -> String {
+    content
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 fn required_groups(template: Template) -> Vec<Vec<String>> {
     match template {
-        Template::Soap => vec![
+        // Progress notes carry the same four required sections as SOAP.
+        Template::Soap | Template::Progress => vec![
             vec!["Subjective".to_string(), "S".to_string()],
             vec!["Objective".to_string(), "O".to_string()],
             vec![
@@ -195,23 +891,39 @@ fn required_groups(template: Template) -> Vec<Vec<String>> {
                 "FollowUp".to_string(),
             ],
         ],
+        Template::Operative => vec![
+            vec![
+                "Preoperative Diagnosis".to_string(),
+                "Preop Dx".to_string(),
+                "Pre-op Diagnosis".to_string(),
+            ],
+            vec![
+                "Postoperative Diagnosis".to_string(),
+                "Postop Dx".to_string(),
+                "Post-op Diagnosis".to_string(),
+            ],
+            vec![
+                "Procedure".to_string(),
+                "Procedure Performed".to_string(),
+                "Operation".to_string(),
+            ],
+            vec!["Findings".to_string(), "Operative Findings".to_string()],
+            vec![
+                "Estimated Blood Loss".to_string(),
+                "EBL".to_string(),
+                "Blood Loss".to_string(),
+            ],
+            vec!["Specimens".to_string(), "Specimen".to_string()],
+        ],
     }
 }
 
-fn known_sections(template: Template) -> HashSet<String> {
-    let mut all = HashSet::new();
-    for group in required_groups(template) {
-        for name in group {
-            all.insert(util::normalize_heading_key(&name));
-        }
-    }
-    let optional = match template {
+fn optional_sections(template: Template) -> Vec<&'static str> {
+    match template {
         Template::Soap => vec!["Narrative"],
         Template::Hp => vec!["Chief Complaint", "ROS", "Review of Systems", "Narrative"],
         Template::Discharge => vec!["Disposition", "Instructions", "Narrative"],
-    };
-    for name in optional {
-        all.insert(util::normalize_heading_key(name));
+        Template::Progress => vec!["Interval History", "Events", "Narrative"],
+        Template::Operative => vec!["Narrative"],
     }
-    all
 }
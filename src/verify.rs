@@ -0,0 +1,116 @@
+use crate::config::Config;
+use crate::parser::{self, normalize, ParseOptions};
+use crate::validate::Template;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedLine {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteVerification {
+    pub note_index: usize,
+    pub total_lines: usize,
+    pub covered_lines: usize,
+    pub dropped_content: Vec<DroppedLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub input: String,
+    pub template: Template,
+    pub notes: Vec<NoteVerification>,
+}
+
+impl VerifyReport {
+    pub fn dropped_count(&self) -> usize {
+        self.notes.iter().map(|n| n.dropped_content.len()).sum()
+    }
+}
+
+/// Checks that every non-blank source line lands inside some detected section.
+/// Line numbers are relative to each note after bundle splitting.
+pub fn verify_document(
+    text: &str,
+    template: Template,
+    config: &Config,
+    input: &str,
+) -> VerifyReport {
+    let (note_texts, _) = parser::split_bundle(text, config.bundle.mode_default, config);
+    let options = ParseOptions {
+        apply_heuristics: config.enable_fallback_heuristics,
+        min_confidence: 0.0,
+    };
+    let notes = note_texts
+        .iter()
+        .enumerate()
+        .map(|(idx, note_text)| verify_note(note_text, template, config, options, idx + 1))
+        .collect();
+    VerifyReport {
+        input: input.to_string(),
+        template,
+        notes,
+    }
+}
+
+fn verify_note(
+    text: &str,
+    template: Template,
+    config: &Config,
+    options: ParseOptions,
+    note_index: usize,
+) -> NoteVerification {
+    let normalized = normalize::normalize_text_with(text, config.normalize.preserve_bullets);
+    let lines: Vec<&str> = normalized.lines().collect();
+    let (candidates, _) = parser::extract_candidates(text, template.note_format(), config, options);
+
+    let mut covered = vec![false; lines.len()];
+    for candidate in &candidates {
+        for line_num in candidate.start_line..=candidate.end_line {
+            if let Some(slot) = covered.get_mut(line_num.saturating_sub(1)) {
+                *slot = true;
+            }
+        }
+    }
+
+    let mut dropped_content = Vec::new();
+    let mut total_lines = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        total_lines += 1;
+        if !covered[idx] {
+            dropped_content.push(DroppedLine {
+                line: idx + 1,
+                text: line.trim().to_string(),
+            });
+        }
+    }
+
+    NoteVerification {
+        note_index,
+        total_lines,
+        covered_lines: total_lines - dropped_content.len(),
+        dropped_content,
+    }
+}
+
+pub fn summarize_text(report: &VerifyReport) -> String {
+    let mut out = String::new();
+    for note in &report.notes {
+        out.push_str(&format!(
+            "Note {}: {}/{} lines covered\n",
+            note.note_index, note.covered_lines, note.total_lines
+        ));
+        for dropped in &note.dropped_content {
+            out.push_str(&format!(
+                "  - dropped line {}: {}\n",
+                dropped.line, dropped.text
+            ));
+        }
+    }
+    out
+}
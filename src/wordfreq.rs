@@ -0,0 +1,68 @@
+use crate::models::StructuredNote;
+use crate::render;
+use crate::util;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WordCount {
+    pub token: String,
+    pub count: usize,
+}
+
+/// Loads every structured JSON note under `dir`, skipping files that are not
+/// note output (such as `batch_report.json`).
+pub fn load_notes(dir: &Path) -> Result<Vec<StructuredNote>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut notes = Vec::new();
+    for path in paths {
+        let content = util::read_to_string(&path)?;
+        if let Ok(many) = render::json::parse_notes(&content) {
+            notes.extend(many);
+        }
+    }
+    Ok(notes)
+}
+
+/// Counts lowercased, punctuation-stripped tokens in the named section,
+/// most frequent first, ties broken alphabetically.
+pub fn word_frequencies(
+    notes: &[StructuredNote],
+    section: &str,
+    min_count: usize,
+) -> Vec<WordCount> {
+    let key = util::normalize_heading_key(section);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for note in notes {
+        for s in &note.sections {
+            if util::normalize_heading_key(s.canonical()) != key {
+                continue;
+            }
+            for token in s
+                .content
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|t| !t.is_empty())
+            {
+                *counts.entry(token.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut words: Vec<WordCount> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(token, count)| WordCount { token, count })
+        .collect();
+    words.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.token.cmp(&b.token)));
+    words
+}
@@ -1,8 +1,40 @@
 use clinote::cli::{run_batch, BatchArgs};
 use clinote::config::Config;
 use clinote::models::NoteFormat;
+use clinote::parser::warnings;
 use clinote::render::OutputFormat;
 use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// JSON output for `*.txt` SOAP notes, everything else off.
+fn batch_args(input_dir: &Path, out_dir: &Path) -> BatchArgs {
+    BatchArgs {
+        input_dir: input_dir.to_path_buf(),
+        glob: Some("*.txt".to_string()),
+        format: NoteFormat::Soap,
+        out_dir: out_dir.to_path_buf(),
+        out_format: vec![OutputFormat::Json],
+        config: None,
+        no_config: false,
+        bundle: None,
+        skip: 0,
+        limit: None,
+        collect_unmapped: None,
+        strict_structure: false,
+        max_warnings: None,
+        out_template: clinote::util::DEFAULT_OUT_TEMPLATE.to_string(),
+        timeout_ms: None,
+        summary_only: false,
+        validate: false,
+        template: None,
+        min_confidence: 0.0,
+        combined: false,
+        dry_run: false,
+        encoding: None,
+        sort_sections: false,
+    }
+}
 
 #[test]
 fn batch_continues_on_failure() {
@@ -16,21 +48,550 @@ fn batch_continues_on_failure() {
     fs::write(input_dir.join("good.txt"), "Subjective:\nAll good").unwrap();
     fs::write(input_dir.join("bad.txt"), [0xff]).unwrap();
 
+    let args = batch_args(&input_dir, &out_dir);
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert_eq!(report.failed_files, 1);
+    assert_eq!(report.failures.len(), 1);
+    assert!(out_dir.join("good.json").exists());
+    assert_eq!(report.encodings.get("utf-8"), Some(&1));
+    assert_eq!(report.encodings.get("unknown"), Some(&1));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_writes_every_requested_format() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_multi_format_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("note.txt"), "Subjective:\nAll good").unwrap();
+
     let args = BatchArgs {
-        input_dir: input_dir.clone(),
-        glob: Some("*.txt".to_string()),
-        format: NoteFormat::Soap,
-        out_dir: out_dir.clone(),
-        out_format: OutputFormat::Json,
-        config: None,
-        bundle: None,
+        out_format: vec![OutputFormat::Md, OutputFormat::Json, OutputFormat::Csv],
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert!(out_dir.join("note.md").exists());
+    assert!(out_dir.join("note.json").exists());
+    assert!(out_dir.join("note.csv").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_skip_and_limit_select_a_sorted_slice() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_slice_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    for name in ["d", "b", "a", "c"] {
+        fs::write(
+            input_dir.join(format!("{}.txt", name)),
+            "Subjective:\nAll good",
+        )
+        .unwrap();
+    }
+
+    let args = BatchArgs {
+        skip: 1,
+        limit: Some(2),
+        ..batch_args(&input_dir, &out_dir)
     };
 
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 2);
+    assert!(!out_dir.join("a.json").exists());
+    assert!(out_dir.join("b.json").exists());
+    assert!(out_dir.join("c.json").exists());
+    assert!(!out_dir.join("d.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_counts_narrative_only_files() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_narrative_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("good.txt"), "Subjective:\nAll good").unwrap();
+    fs::write(input_dir.join("blob.txt"), "just some unstructured text").unwrap();
+
+    let mut args = batch_args(&input_dir, &out_dir);
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 2);
+    assert_eq!(report.narrative_only_files, 1);
+
+    args.strict_structure = true;
+    let _ = fs::remove_dir_all(&out_dir);
     let report = run_batch(&args, &Config::default()).unwrap();
     assert_eq!(report.ok_files, 1);
     assert_eq!(report.failed_files, 1);
-    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.narrative_only_files, 1);
     assert!(out_dir.join("good.json").exists());
+    assert!(!out_dir.join("blob.json").exists());
+
+    // One unstructured note is enough to fail a bundle file.
+    fs::write(
+        input_dir.join("bundle.txt"),
+        "Subjective:\nok\n----- NOTE -----\nmore unstructured text",
+    )
+    .unwrap();
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert_eq!(report.failed_files, 2);
+    assert!(!out_dir.join("bundle.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_ranks_unmapped_headings() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_unmapped_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(
+        input_dir.join("a.txt"),
+        "Subjective:\nok\nHPI: cough\nPMH:\nnone",
+    )
+    .unwrap();
+    fs::write(input_dir.join("b.txt"), "Subjective:\nok\nHPI: fever").unwrap();
+
+    let args = batch_args(&input_dir, &out_dir);
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    let ranking = report.unmapped_ranking();
+    assert_eq!(ranking.len(), 2);
+    assert_eq!((ranking[0].heading.as_str(), ranking[0].count), ("HPI", 2));
+    assert_eq!((ranking[1].heading.as_str(), ranking[1].count), ("PMH", 1));
+
+    // The heading is read from the warning's `heading` field, not its message.
+    let mut warning = warnings::unmapped_heading("Social Hx", 3, 4);
+    warning.message = "reworded".to_string();
+    assert_eq!(warnings::unmapped_heading_text(&warning), Some("Social Hx"));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_honors_clinoteignore() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_ignore_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("keep.txt"), "Subjective:\nok").unwrap();
+    fs::write(input_dir.join("README.txt"), "not a note").unwrap();
+    fs::write(input_dir.join(".clinoteignore"), "README*\n").unwrap();
+
+    let args = batch_args(&input_dir, &out_dir);
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert!(out_dir.join("keep.json").exists());
+    assert!(!out_dir.join("README.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_report_computes_runtime_percentiles() {
+    let mut report = clinote::reports::BatchReport::new("clinote");
+    for ms in 1..=100u128 {
+        report.record_timing(&format!("f{}.txt", ms), ms);
+    }
+    report.finalize();
+    assert_eq!(report.runtime_stats.p50_ms, 50);
+    assert_eq!(report.runtime_stats.p90_ms, 90);
+    assert_eq!(report.runtime_stats.p99_ms, 99);
+    assert_eq!(
+        report.runtime_stats.slowest_file.as_deref(),
+        Some("f100.txt")
+    );
+
+    let path = std::env::temp_dir().join("clinote_batch_report_stats.csv");
+    report.write_csv_to(&path).unwrap();
+    let csv = fs::read_to_string(&path).unwrap();
+    assert!(csv.contains("p90_ms,90"));
+    assert!(csv.contains("slowest_file,f100.txt"));
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn batch_expands_out_template() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_out_template_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("a.txt"), "Subjective:\nok").unwrap();
+    fs::write(input_dir.join("b.txt"), "Subjective:\nok").unwrap();
+
+    let args = BatchArgs {
+        out_format: vec![OutputFormat::Json, OutputFormat::Md],
+        out_template: "{format}/{ext}/{index}-{stem}.{ext}".to_string(),
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 2);
+    assert!(out_dir.join("soap/json/1-a.json").exists());
+    assert!(out_dir.join("soap/md/2-b.md").exists());
+    assert!(!out_dir.join("a.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_timeout_runs_files_on_a_worker() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_timeout_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("good.txt"), "Subjective:\nAll good").unwrap();
+    fs::write(input_dir.join("bad.txt"), [0xff]).unwrap();
+
+    let args = BatchArgs {
+        timeout_ms: Some(60_000),
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert_eq!(report.failed_files, 1);
+    assert_eq!(report.timed_out_files, 0);
+    assert_eq!(report.encodings.get("unknown"), Some(&1));
+    assert!(out_dir.join("good.json").exists());
+
+    let args = BatchArgs {
+        timeout_ms: Some(0),
+        ..args
+    };
+    let _ = fs::remove_dir_all(&out_dir);
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.timed_out_files, 2);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(!out_dir.join("good.json").exists());
+
+    let mut report = clinote::reports::BatchReport::new("clinote");
+    report.record_timeout("slow.txt", 250);
+    assert_eq!(report.timed_out_files, 1);
+    assert_eq!(report.failed_files, 1);
+    assert!(report.failures[0].error.contains("timeout"));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_summary_only_skips_rendered_outputs() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_summary_only_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::create_dir_all(&out_dir).unwrap();
+
+    fs::write(input_dir.join("note.txt"), "Subjective:\nok\nPlan:\nrest").unwrap();
+
+    let args = BatchArgs {
+        out_format: Vec::new(),
+        summary_only: true,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert_eq!(report.counts_by_section.get("Plan"), Some(&1));
+    assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 0);
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_validate_collects_issues_per_file_and_note() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_validate_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(
+        input_dir.join("bundle.txt"),
+        "Subjective:\nok\n----- NOTE -----\nPlan:\nrest",
+    )
+    .unwrap();
+
+    let args = BatchArgs {
+        out_format: Vec::new(),
+        summary_only: true,
+        validate: true,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.validation.len(), 1);
+    let file = &report.validation[0];
+    assert!(file.file.ends_with("bundle.txt"));
+    assert_eq!(file.notes.len(), 2);
+    assert_eq!(file.notes[1].note_index, 2);
+    assert!(file.notes[1]
+        .issues
+        .iter()
+        .any(|i| i.code == "missing_required" && i.section.as_deref() == Some("Subjective")));
+    assert_eq!(
+        report.validation_warnings_by_code.get("section_too_short"),
+        Some(&2)
+    );
+    assert!(report.validation_errors_by_code.is_empty());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_reads_gzipped_inputs() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_gzip_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    let mut encoder = flate2::write::GzEncoder::new(
+        fs::File::create(input_dir.join("note.txt.gz")).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder
+        .write_all(b"Subjective:\nAll good\nPlan:\nRest")
+        .unwrap();
+    encoder.finish().unwrap();
+
+    let args = BatchArgs {
+        glob: Some("*.txt.gz".to_string()),
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    let json = fs::read_to_string(out_dir.join("note.json")).unwrap();
+    assert!(json.contains("All good"));
+    assert!(json.contains("\"Plan\""));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_combined_collects_notes_in_file_order() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_combined_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("b.txt"), "Plan:\nRest").unwrap();
+    fs::write(
+        input_dir.join("a.txt"),
+        "Subjective:\nOne\n----- NOTE -----\nSubjective:\nTwo",
+    )
+    .unwrap();
+
+    let args = BatchArgs {
+        combined: true,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    let contents: Vec<_> = report
+        .combined
+        .iter()
+        .map(|note| note.sections[0].content.as_str())
+        .collect();
+    assert_eq!(contents, vec!["One", "Two", "Rest"]);
+    assert!(report.combined[2]
+        .source_file
+        .as_deref()
+        .unwrap()
+        .ends_with("b.txt"));
+    assert!(out_dir.join("a.json").exists());
+    assert!(out_dir.join("b.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_dry_run_creates_no_files() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_dry_run_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("note.txt"), "Subjective:\nAll good").unwrap();
+
+    let args = BatchArgs {
+        out_format: vec![OutputFormat::Md, OutputFormat::Json],
+        dry_run: true,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    assert_eq!(report.counts_by_section.get("Subjective"), Some(&1));
+    assert!(!out_dir.exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn quiet_and_verbose_are_global_and_exclusive() {
+    use clap::Parser;
+    use clinote::cli::Cli;
+    use clinote::logging::Verbosity;
+
+    let cli = Cli::try_parse_from([
+        "clinote",
+        "batch",
+        "--input-dir",
+        "in",
+        "--format",
+        "soap",
+        "--out-dir",
+        "out",
+        "--out-format",
+        "json",
+        "-v",
+    ])
+    .unwrap();
+    assert_eq!(
+        Verbosity::from_flags(cli.quiet, cli.verbose),
+        Verbosity::Verbose
+    );
+    let cli = Cli::try_parse_from(["clinote", "-q", "demo"]).unwrap();
+    assert_eq!(
+        Verbosity::from_flags(cli.quiet, cli.verbose),
+        Verbosity::Quiet
+    );
+    assert!(Cli::try_parse_from(["clinote", "-q", "-v", "demo"]).is_err());
+}
+
+#[test]
+fn batch_decodes_windows_1252_with_encoding() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_encoding_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    // "Temp 38\u{b0}C" with the degree sign as the single latin-1 byte 0xB0.
+    fs::write(
+        input_dir.join("latin1.txt"),
+        b"Subjective:\nTemp 38\xb0C\nPlan:\nRest",
+    )
+    .unwrap();
+
+    let mut args = BatchArgs {
+        glob: None,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.failed_files, 1);
+
+    args.encoding = Some("windows-1252".to_string());
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 1);
+    let json = fs::read_to_string(out_dir.join("latin1.json")).unwrap();
+    assert!(json.contains("Temp 38\u{b0}C"));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn batch_glob_expands_brace_alternations() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_brace_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("a.txt"), "Subjective:\nCough\nPlan:\nRest").unwrap();
+    fs::write(
+        input_dir.join("b.note"),
+        "Subjective:\nFever\nPlan:\nFluids",
+    )
+    .unwrap();
+    fs::write(input_dir.join("c.md"), "Subjective:\nSkipped\nPlan:\nNone").unwrap();
+
+    let args = BatchArgs {
+        glob: Some("*.{txt,note,txt}".to_string()),
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.ok_files, 2);
+    assert_eq!(report.failed_files, 0);
+    assert!(out_dir.join("a.json").exists());
+    assert!(out_dir.join("b.json").exists());
+    assert!(!out_dir.join("c.json").exists());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn sort_sections_placeholders_do_not_count_as_detected() {
+    let temp_dir = std::env::temp_dir().join("clinote_batch_sort_sections_test");
+    let input_dir = temp_dir.join("in");
+    let out_dir = temp_dir.join("out");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&input_dir).unwrap();
+
+    fs::write(input_dir.join("blob.txt"), "just some unstructured text").unwrap();
+    fs::write(
+        input_dir.join("partial.txt"),
+        "Subjective:\nCough for three days",
+    )
+    .unwrap();
+
+    let args = BatchArgs {
+        no_config: true,
+        strict_structure: true,
+        combined: true,
+        sort_sections: true,
+        ..batch_args(&input_dir, &out_dir)
+    };
+
+    let report = run_batch(&args, &Config::default()).unwrap();
+    assert_eq!(report.narrative_only_files, 1);
+    assert_eq!(report.ok_files, 1);
+    assert!(!out_dir.join("blob.json").exists());
+    assert_eq!(report.counts_by_section.get("Subjective"), Some(&1));
+    assert!(!report.counts_by_section.contains_key("Objective"));
+
+    let written = fs::read_to_string(out_dir.join("partial.json")).unwrap();
+    assert!(written.contains("\"Objective\""));
+    let names: Vec<&str> = report.combined[0]
+        .sections
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(names, ["Subjective", "Objective", "Assessment", "Plan"]);
 
     let _ = fs::remove_dir_all(&temp_dir);
 }
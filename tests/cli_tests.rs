@@ -0,0 +1,272 @@
+use clinote::config::Config;
+use clinote::models::BundleMode;
+use clinote::parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn clinote(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_clinote"))
+        .args(args)
+        .env_remove("CLINOTE_CONFIG")
+        .output()
+        .unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn path_arg(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn validate_reports_parser_warnings_with_issues() {
+    let dir = temp_dir("clinote_cli_validate_warnings_test");
+    let input = dir.join("blob.txt");
+    fs::write(&input, "just some unstructured text").unwrap();
+
+    let output = clinote(&["validate", path_arg(&input), "--no-config", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let note = &json["files"][0]["reports"][0];
+    assert!(note["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w["code"] == "no_headings" && w["severity"] == "warning"));
+    assert!(note["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|i| i["code"] == "missing_required"));
+
+    let output = clinote(&["validate", path_arg(&input), "--no-config"]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        text.contains(
+            "- Warning (parser): No headings detected; content grouped as Narrative [lines 1-1]"
+        ),
+        "{}",
+        text
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn validate_reports_every_input_file() {
+    let dir = temp_dir("clinote_cli_validate_files_test");
+    let complete = dir.join("complete.txt");
+    let partial = dir.join("partial.txt");
+    fs::write(
+        &complete,
+        "Visit Date: 2024-01-02\nSubjective:\nCough for three days now\nObjective:\nLungs clear bilaterally today\nAssessment:\nViral upper respiratory infection\nPlan:\nRest and fluids, return in 7 days",
+    )
+    .unwrap();
+    fs::write(&partial, "Subjective:\nCough for three days now").unwrap();
+
+    for inputs in [vec![&complete], vec![&complete, &partial]] {
+        let mut args = vec!["validate", "--no-config", "--json"];
+        args.extend(inputs.iter().map(|path| path_arg(path)));
+        let output = clinote(&args);
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json["template"], "soap");
+        let files = json["files"].as_array().unwrap();
+        assert_eq!(files.len(), inputs.len());
+        assert_eq!(files[0]["input"], path_arg(&complete));
+        assert_eq!(files[0]["reports"][0]["issues"], serde_json::json!([]));
+    }
+
+    let output = clinote(&[
+        "validate",
+        "--no-config",
+        "--json",
+        path_arg(&complete),
+        path_arg(&partial),
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let issues = json["files"][1]["reports"][0]["issues"].as_array().unwrap();
+    assert!(issues.iter().any(|i| i["code"] == "missing_required"));
+    assert_eq!(json["has_warnings"], true);
+
+    let output = clinote(&[
+        "validate",
+        "--no-config",
+        path_arg(&complete),
+        path_arg(&partial),
+    ]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains(&format!("== {} ==", path_arg(&complete))));
+    assert!(text.contains(&format!("== {} ==", path_arg(&partial))));
+
+    let missing = dir.join("missing.txt");
+    let output = clinote(&[
+        "validate",
+        "--no-config",
+        "--json",
+        path_arg(&complete),
+        path_arg(&missing),
+        path_arg(&partial),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let files = json["files"].as_array().unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(files[0].get("error").is_none());
+    assert_eq!(files[1]["input"], path_arg(&missing));
+    assert!(files[1]["error"].is_string());
+    assert_eq!(files[1]["reports"], serde_json::json!([]));
+    assert!(files[2]["reports"][0]["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|i| i["code"] == "missing_required"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn resolve_heading_checks_format_aliases() {
+    let dir = temp_dir("clinote_cli_resolve_heading_test");
+    let config_path = dir.join("clinote.toml");
+    let mut config = Config::default();
+    config
+        .formats
+        .discharge
+        .heading_aliases
+        .insert("Impression".to_string(), "Discharge Diagnosis".to_string());
+    fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+    let resolve = |extra: &[&str]| {
+        let mut args = vec![
+            "resolve-heading",
+            "Impression",
+            "--config",
+            path_arg(&config_path),
+        ];
+        args.extend_from_slice(extra);
+        let output = clinote(&args);
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    assert_eq!(
+        resolve(&["--format", "discharge"]),
+        "Discharge Diagnosis (alias 'Impression')"
+    );
+    assert_eq!(resolve(&[]), "'Impression' is unmapped");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn preview_reports_bundle_warnings() {
+    let dir = temp_dir("clinote_cli_preview_bundle_test");
+    let config_path = dir.join("clinote.toml");
+    let mut config = Config::default();
+    config.bundle.strip_banners = true;
+    fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+    let input = dir.join("bundle.txt");
+    fs::write(
+        &input,
+        "MERCY GENERAL HOSPITAL\nSubjective:\ncough\n----- NOTE -----\nMERCY GENERAL HOSPITAL\nPlan:\nrest\n",
+    )
+    .unwrap();
+
+    let output = clinote(&[
+        "preview",
+        path_arg(&input),
+        "--json",
+        "--config",
+        path_arg(&config_path),
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let notes = json.as_array().unwrap();
+    assert_eq!(notes.len(), 2);
+    assert!(notes.iter().all(|note| note["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w["code"] == "stripped_banner")));
+
+    let output = clinote(&[
+        "preview",
+        path_arg(&input),
+        "--config",
+        path_arg(&config_path),
+    ]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("(stripped_banner)"), "{}", text);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn validate_exit_codes_match_help() {
+    let dir = temp_dir("clinote_cli_validate_exit_test");
+    let complete = dir.join("complete.txt");
+    let partial = dir.join("partial.txt");
+    fs::write(
+        &complete,
+        "Visit Date: 2024-01-02\nSubjective:\nCough for three days now\nObjective:\nLungs clear bilaterally today\nAssessment:\nViral upper respiratory infection\nPlan:\nRest and fluids, return in 7 days",
+    )
+    .unwrap();
+    fs::write(
+        &partial,
+        "Visit Date: 2024-01-02\nSubjective:\nCough for three days now",
+    )
+    .unwrap();
+    let missing = dir.join("missing.txt");
+
+    let code = |args: &[&str]| {
+        let mut full = vec!["validate", "--no-config"];
+        full.extend_from_slice(args);
+        clinote(&full).status.code()
+    };
+    assert_eq!(code(&[path_arg(&complete)]), Some(0));
+    assert_eq!(code(&[path_arg(&missing)]), Some(1));
+    assert_eq!(code(&[path_arg(&partial), "--strict"]), Some(2));
+    assert_eq!(code(&[path_arg(&partial), "--fail-on-warning"]), Some(3));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn merged_notes_split_back_into_the_same_notes() {
+    let dir = temp_dir("clinote_cli_merge_test");
+    let visits = [
+        "Subjective:\nCough\nPlan:\nRest\n",
+        "Subjective:\nFever\nPlan:\nFluids",
+        "\nSubjective:\nBetter\nPlan:\nNone\n\n",
+    ];
+    let mut inputs = Vec::new();
+    for (idx, visit) in visits.iter().enumerate() {
+        let path = dir.join(format!("visit{}.txt", idx + 1));
+        fs::write(&path, visit).unwrap();
+        inputs.push(path);
+    }
+
+    let out = dir.join("bundle.txt");
+    let mut args = vec!["merge", "--no-config", "--out", path_arg(&out)];
+    args.extend(inputs.iter().map(|path| path_arg(path)));
+    assert!(clinote(&args).status.success());
+    let merged = fs::read_to_string(&out).unwrap();
+    assert_eq!(merged.matches("----- NOTE -----").count(), 2);
+
+    let config = Config::default();
+    let (notes, warnings) = parser::split_bundle(&merged, BundleMode::On, &config);
+    assert!(warnings.is_empty());
+    let expected: Vec<String> = visits.iter().map(|v| v.trim().to_string()).collect();
+    assert_eq!(notes, expected);
+
+    let custom = parser::merge_bundle(&visits, "### NEXT ###");
+    let mut config = Config::default();
+    config.bundle.delimiters = vec!["### NEXT ###".to_string()];
+    let (notes, _) = parser::split_bundle(&custom, BundleMode::On, &config);
+    assert_eq!(notes.len(), 3);
+
+    let _ = fs::remove_dir_all(&dir);
+}
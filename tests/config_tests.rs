@@ -1,5 +1,45 @@
 use clinote::config::Config;
-use clinote::models::SectionName;
+use clinote::models::{CsvLayout, NoteFormat, SectionName};
+use std::ffi::{OsStr, OsString};
+use std::sync::{Mutex, MutexGuard};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Holds `ENV_LOCK` so tests touching the environment run one at a time, and
+/// puts back every variable it changed when dropped.
+struct EnvGuard {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    fn lock() -> Self {
+        EnvGuard {
+            saved: Vec::new(),
+            _lock: ENV_LOCK
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        }
+    }
+
+    fn set(&mut self, key: &'static str, value: impl AsRef<OsStr>) {
+        if !self.saved.iter().any(|(saved, _)| *saved == key) {
+            self.saved.push((key, std::env::var_os(key)));
+        }
+        std::env::set_var(key, value);
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, value) in self.saved.drain(..).rev() {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+}
 
 fn full_config_toml() -> String {
     r#"
@@ -50,10 +90,7 @@ glob_default = "*.txt"
 fn parse_config_ok() {
     let config: Config = toml::from_str(&full_config_toml()).unwrap();
     assert!(config.enable_fallback_heuristics);
-    assert_eq!(
-        config.formats.soap.section_order[0],
-        SectionName::Subjective
-    );
+    assert_eq!(config.formats.soap.section_order[0], "Subjective");
     assert_eq!(
         config.bundle.mode_default,
         clinote::models::BundleMode::Auto
@@ -67,10 +104,10 @@ fn default_config_has_delimiters() {
 }
 
 #[test]
-fn invalid_section_name_errors() {
+fn empty_section_name_errors() {
     let toml_str = r#"
 [formats.soap]
-section_order = ["NotASection"]
+section_order = [" "]
 
 [formats.hp]
 section_order = ["Chief Complaint"]
@@ -90,3 +127,235 @@ glob_default = "*.txt"
     let result: Result<Config, _> = toml::from_str(toml_str);
     assert!(result.is_err());
 }
+
+#[test]
+fn parse_csv_layout_by_format() {
+    let toml_str = full_config_toml().replace(
+        "[csv]\nlayout = \"wide\"\n",
+        "[csv]\nlayout = \"wide\"\nby_format = { discharge = \"long\" }\n",
+    );
+    let config: Config = toml::from_str(&toml_str).unwrap();
+    assert_eq!(
+        config.csv.by_format.get(&NoteFormat::Discharge),
+        Some(&CsvLayout::Long)
+    );
+}
+
+#[test]
+fn section_order_missing_required_is_flagged() {
+    assert!(clinote::validate::validate_config(&Config::default()).is_empty());
+
+    let mut config = Config::default();
+    config
+        .formats
+        .hp
+        .section_order
+        .retain(|name| name != SectionName::Plan.as_str());
+    let issues = clinote::validate::validate_config(&config);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].code, "section_order_missing_required");
+    assert_eq!(issues[0].section.as_deref(), Some("Plan"));
+}
+
+#[test]
+fn custom_template_sections_missing_from_order_are_flagged() {
+    let mut config = Config::default();
+    config.templates.push(clinote::config::CustomTemplate {
+        name: "Nursing Assessment".to_string(),
+        format: NoteFormat::Hp,
+        required_sections: vec![
+            vec!["Assessment".to_string()],
+            vec!["Plan".to_string(), "Interventions".to_string()],
+            vec!["Interventions".to_string()],
+        ],
+        optional_sections: vec!["Allergies".to_string(), "Wound Care".to_string()],
+    });
+    let issues = clinote::validate::validate_config(&config);
+    let sections: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.code == "template_section_not_in_order")
+        .filter_map(|i| i.section.as_deref())
+        .collect();
+    assert_eq!(sections, ["Interventions", "Wound Care"]);
+    assert!(issues[0].message.contains("formats.hp.section_order"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn search_paths_prefer_local_then_xdg() {
+    let mut env = EnvGuard::lock();
+    let xdg = std::env::temp_dir().join("clinote_xdg_test");
+    env.set("XDG_CONFIG_HOME", &xdg);
+    let paths = Config::search_paths();
+    assert_eq!(paths[0], std::path::PathBuf::from("clinote.toml"));
+    assert_eq!(paths[1], xdg.join("clinote").join("config.toml"));
+}
+
+#[test]
+fn custom_section_names_are_accepted() {
+    let toml_str = full_config_toml().replace(
+        "section_order = [\"Subjective\", \"Objective\", \"Assessment\", \"Plan\"]",
+        "section_order = [\"S\", \"Objective\", \"Social History\", \"Assessment\", \"Plan\"]",
+    );
+    let config: Config = toml::from_str(&toml_str).unwrap();
+    assert_eq!(
+        config.formats.soap.section_order,
+        vec![
+            "Subjective",
+            "Objective",
+            "Social History",
+            "Assessment",
+            "Plan"
+        ]
+    );
+}
+
+#[test]
+fn default_template_round_trips_to_defaults() {
+    let defaults = toml::to_string(&Config::default()).unwrap();
+    for full in [false, true] {
+        let template = clinote::config::default_template(full).unwrap();
+        let parsed: Config = toml::from_str(&template).unwrap();
+        assert_eq!(toml::to_string(&parsed).unwrap(), defaults);
+    }
+    let full = clinote::config::default_template(true).unwrap();
+    assert!(full.contains("# narrative_chunk_chars = 2000"));
+    assert!(full.contains("# \"Hx\" = \"PMH\""));
+}
+
+#[test]
+fn default_config_json_matches_defaults() {
+    let json = clinote::config::default_config_json().unwrap();
+    let parsed: Config = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        toml::to_string(&parsed).unwrap(),
+        toml::to_string(&Config::default()).unwrap()
+    );
+}
+
+#[test]
+fn per_format_heading_aliases_parse_alongside_global_ones() {
+    let toml = full_config_toml().replace(
+        "heading_aliases = { \"Hx\" = \"PMH\", \"Dx\" = \"Assessment\" }\n",
+        "heading_aliases = { \"Dx\" = \"Discharge Dx\" }\n",
+    ) + "\n[heading_aliases]\n\"Hx\" = \"PMH\"\n";
+    let config: Config = toml::from_str(&toml).unwrap();
+    assert_eq!(
+        config.resolve_heading_alias_entry_for("dx", Some(NoteFormat::Discharge)),
+        Some(("Dx".to_string(), "Discharge Dx".to_string()))
+    );
+    assert_eq!(
+        config.resolve_heading_alias_entry_for("dx", Some(NoteFormat::Soap)),
+        None
+    );
+    assert_eq!(config.resolve_heading_alias("Hx").as_deref(), Some("PMH"));
+    assert!(config.formats.soap.heading_aliases.is_empty());
+}
+
+#[test]
+fn colliding_heading_aliases_resolve_deterministically() {
+    let mut config = Config::default();
+    config.heading_aliases.clear();
+    for (key, value) in [("Dx", "Assessment"), ("DX:", "Plan"), ("dx ", "HPI")] {
+        config
+            .heading_aliases
+            .insert(key.to_string(), value.to_string());
+    }
+    let entry = |raw: &str| config.resolve_heading_alias_entry(raw).unwrap().0;
+    assert_eq!(entry("Dx"), "Dx");
+    assert_eq!(entry("DX:"), "DX:");
+    assert_eq!(entry("dx"), "DX:");
+    assert_eq!(entry("dX"), "DX:");
+}
+
+#[test]
+fn custom_templates_accept_names_or_alias_lists() {
+    let toml = full_config_toml()
+        + r#"
+[[templates]]
+name = "Nursing Assessment"
+format = "hp"
+required_sections = ["Assessment", ["Plan", "Interventions"], "Meds"]
+optional_sections = ["Allergies"]
+"#;
+    let config: Config = toml::from_str(&toml).unwrap();
+    let template = config.custom_template("nursing assessment").unwrap();
+    assert_eq!(template.format, NoteFormat::Hp);
+    assert_eq!(
+        template.required_sections,
+        vec![
+            vec!["Assessment".to_string()],
+            vec!["Plan".to_string(), "Interventions".to_string()],
+            vec!["Medications".to_string()],
+        ]
+    );
+    assert!(config.custom_template("Wound Care").is_none());
+
+    let bad = full_config_toml() + "\n[[templates]]\nname = \"Empty\"\nrequired_sections = [[]]\n";
+    assert!(toml::from_str::<Config>(&bad).is_err());
+    assert!(!clinote::config::default_template(false)
+        .unwrap()
+        .contains("[[templates]]"));
+}
+
+#[test]
+fn config_env_var_is_used_when_no_path_is_given() {
+    use clinote::config::CONFIG_ENV_VAR;
+    let temp_dir = std::env::temp_dir().join("clinote_config_env_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+    let path = temp_dir.join("env.toml");
+    let config = Config {
+        glob_default: "*.note".to_string(),
+        ..Config::default()
+    };
+    std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+
+    let mut env = EnvGuard::lock();
+    env.set(CONFIG_ENV_VAR, &path);
+    let loaded = Config::load(None).unwrap();
+    assert_eq!(loaded.glob_default, "*.note");
+
+    // An explicit path still wins over the environment.
+    let explicit = temp_dir.join("explicit.toml");
+    std::fs::write(&explicit, toml::to_string(&Config::default()).unwrap()).unwrap();
+    let loaded = Config::load(Some(&explicit)).unwrap();
+    assert_eq!(loaded.glob_default, Config::default().glob_default);
+
+    env.set(CONFIG_ENV_VAR, temp_dir.join("missing.toml"));
+    let err = Config::load(None).unwrap_err();
+    assert!(err.to_string().contains("missing.toml"));
+
+    drop(env);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn invalid_rule_pattern_fails_config_load() {
+    let dir = std::env::temp_dir().join("clinote_invalid_rule_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("clinote.toml");
+    let mut config = Config::default();
+    config.validation.rules.push(clinote::config::ContentRule {
+        section: "Plan".to_string(),
+        pattern: "(days".to_string(),
+        severity: clinote::models::Severity::Warn,
+        code: "plan_missing_follow_up".to_string(),
+        message: "Plan has no follow-up interval".to_string(),
+        must_match: true,
+    });
+    std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+
+    let err = Config::load(Some(&path)).unwrap_err().to_string();
+    assert!(err.contains("plan_missing_follow_up"), "{}", err);
+    assert!(err.contains("(days"), "{}", err);
+    assert!(clinote::validate::validate_config(&config)
+        .iter()
+        .any(|i| i.code == "invalid_rule_pattern"));
+
+    config.validation.rules[0].pattern = "\\d+ days".to_string();
+    std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+    assert!(Config::load(Some(&path)).is_ok());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,85 @@
+use clinote::config::Config;
+use clinote::diff;
+use clinote::models::NoteFormat;
+use clinote::parser::{self, ParseOptions};
+use std::fs;
+
+fn parse(text: &str, format: NoteFormat, note_index: usize) -> clinote::models::StructuredNote {
+    let options = ParseOptions {
+        apply_heuristics: true,
+        min_confidence: 0.0,
+    };
+    parser::parse_note(text, format, &Config::default(), None, note_index, options)
+}
+
+#[test]
+fn diff_reports_section_and_format_changes() {
+    let before = vec![
+        parse("Subjective:\nCough\nPlan:\nRest", NoteFormat::Soap, 1),
+        parse("Subjective:\nSame", NoteFormat::Soap, 2),
+        parse("Plan:\nGone later", NoteFormat::Soap, 3),
+    ];
+    let after = vec![
+        parse(
+            "Subjective:\nCough and fever\nAssessment:\nViral",
+            NoteFormat::Soap,
+            1,
+        ),
+        parse("Subjective:\nSame", NoteFormat::Progress, 2),
+    ];
+
+    let report = diff::diff_notes("note", &before, &after);
+    assert_eq!(report.notes_compared, 2);
+    assert_eq!(report.removed_notes.len(), 1);
+    assert_eq!(report.removed_notes[0].note_index, 3);
+    assert!(report.added_notes.is_empty());
+
+    let first = &report.changed_notes[0];
+    assert_eq!(first.note_index, 1);
+    assert_eq!(first.added_sections, vec!["Assessment".to_string()]);
+    assert_eq!(first.removed_sections, vec!["Plan".to_string()]);
+    assert_eq!(first.changed_sections.len(), 1);
+    assert_eq!(first.changed_sections[0].before, "Cough");
+    assert_eq!(first.changed_sections[0].after, "Cough and fever");
+
+    let second = &report.changed_notes[1];
+    assert!(second.changed_sections.is_empty());
+    let change = second.format.as_ref().unwrap();
+    assert_eq!(
+        (change.before, change.after),
+        (NoteFormat::Soap, NoteFormat::Progress)
+    );
+
+    let summary = diff::format_summary(&report);
+    assert!(summary.starts_with("Compared 2 notes: 2 changed, 0 added, 1 removed"));
+    assert!(summary.contains("format: soap -> progress"));
+
+    let same = diff::diff_notes("note", &before, &before);
+    assert!(same.is_empty());
+    assert!(diff::format_summary(&same).contains("No differences"));
+}
+
+#[test]
+fn diff_pairs_directory_files_by_name() {
+    let temp_dir = std::env::temp_dir().join("clinote_diff_dir_test");
+    let (left, right) = (temp_dir.join("before"), temp_dir.join("after"));
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&left).unwrap();
+    fs::create_dir_all(&right).unwrap();
+
+    let note = parse("Plan:\nRest", NoteFormat::Soap, 1);
+    let json = serde_json::to_string_pretty(&note).unwrap();
+    fs::write(left.join("a.json"), &json).unwrap();
+    fs::write(right.join("a.json"), &json).unwrap();
+    fs::write(right.join("b.json"), &json).unwrap();
+    fs::write(right.join("batch_report.json"), "{\"ok_files\": 1}").unwrap();
+
+    let report = diff::diff_paths(&left, &right).unwrap();
+    assert_eq!(report.notes_compared, 1);
+    assert!(report.changed_notes.is_empty());
+    assert_eq!(report.added_notes.len(), 1);
+    assert_eq!(report.added_notes[0].file, "b.json");
+    assert!(diff::diff_paths(&left, &right.join("a.json")).is_err());
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
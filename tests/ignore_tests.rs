@@ -0,0 +1,23 @@
+use clinote::ignore::IgnoreList;
+use std::path::Path;
+
+#[test]
+fn ignore_patterns_follow_gitignore_shapes() {
+    let root = Path::new("notes");
+    let list = IgnoreList::parse(root, "# comment\n\nREADME*\narchive/\ntemplates/*.txt\n");
+    assert!(list.is_ignored(&root.join("README.txt")));
+    assert!(list.is_ignored(&root.join("sub").join("README.md")));
+    assert!(list.is_ignored(&root.join("archive").join("old.txt")));
+    assert!(list.is_ignored(&root.join("a").join("archive").join("old.txt")));
+    assert!(!list.is_ignored(&root.join("archive")));
+    assert!(list.is_ignored(&root.join("templates").join("soap.txt")));
+    assert!(!list.is_ignored(&root.join("x").join("templates").join("soap.txt")));
+    assert!(!list.is_ignored(&root.join("visit.txt")));
+}
+
+#[test]
+fn missing_ignore_file_ignores_nothing() {
+    let list = IgnoreList::load(Path::new("does/not/exist"));
+    assert!(list.is_empty());
+    assert!(!list.is_ignored(Path::new("does/not/exist/a.txt")));
+}
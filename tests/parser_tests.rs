@@ -1,9 +1,18 @@
 use clinote::config::Config;
-use clinote::models::{BundleMode, NoteFormat};
+use clinote::models::{BundleMode, DuplicatePolicy, HeadingMethod, NoteFormat, SectionCandidate};
 use clinote::parser;
 use clinote::parser::headings;
 use clinote::parser::sectionize;
 
+const HEURISTICS: parser::ParseOptions = parser::ParseOptions {
+    apply_heuristics: true,
+    min_confidence: 0.0,
+};
+const NO_HEURISTICS: parser::ParseOptions = parser::ParseOptions {
+    apply_heuristics: false,
+    min_confidence: 0.0,
+};
+
 #[test]
 fn detects_heading_with_alias() {
     let mut config = Config::default();
@@ -32,3 +41,977 @@ fn bundle_splits_on_delimiter() {
     let (notes, _warnings) = parser::split_bundle(text, BundleMode::On, &config);
     assert_eq!(notes.len(), 2);
 }
+
+#[test]
+fn bundle_delimiters_ignore_case_and_spacing() {
+    let mut config = Config::default();
+    let text = "Note one\n  -----   note  -----\nNote two";
+    let (notes, _warnings) = parser::split_bundle(text, BundleMode::On, &config);
+    assert_eq!(notes, vec!["Note one".to_string(), "Note two".to_string()]);
+
+    config.bundle.case_sensitive_delimiters = true;
+    let (notes, _warnings) = parser::split_bundle(text, BundleMode::On, &config);
+    assert_eq!(notes.len(), 1);
+}
+
+#[test]
+fn content_hash_is_stable_across_runs() {
+    let config = Config::default();
+    let text = "Subjective:\nFeels well\nPlan:\nFollow up in two weeks";
+    let options = HEURISTICS;
+    let first = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let second = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(first.content_hash.len(), 64);
+    assert_eq!(first.content_hash, second.content_hash);
+
+    let changed = parser::parse_note(
+        "Subjective:\nFeels worse\nPlan:\nFollow up in two weeks",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        options,
+    );
+    assert_ne!(first.content_hash, changed.content_hash);
+}
+
+#[test]
+fn detects_heading_with_trailing_parenthetical() {
+    let config = Config::default();
+    let heading = headings::detect_heading("Assessment (and Plan):", &config).unwrap();
+    assert_eq!(heading.0, "Assessment");
+
+    let inline = headings::detect_heading("HPI (per patient): cough for 3 days", &config).unwrap();
+    assert_eq!(inline.0, "HPI");
+    assert_eq!(inline.1.as_deref(), Some("cough for 3 days"));
+
+    assert!(headings::detect_heading("Unknown (and Plan):", &config).is_none());
+}
+
+#[test]
+fn strip_trailing_parenthetical_is_trailing_only() {
+    assert_eq!(
+        clinote::util::strip_trailing_parenthetical("Assessment (and Plan)"),
+        "Assessment"
+    );
+    assert_eq!(
+        clinote::util::strip_trailing_parenthetical("Plan (A) details"),
+        "Plan (A) details"
+    );
+    assert_eq!(
+        clinote::util::strip_trailing_parenthetical("(PRN)"),
+        "(PRN)"
+    );
+}
+
+#[test]
+fn sections_record_how_their_heading_was_detected() {
+    let config = Config::default();
+    let text = "SUBJECTIVE\nFeels well\nObjective:\nAfebrile\nPlan: rest and fluids";
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, HEURISTICS);
+    let methods: Vec<_> = note.sections.iter().map(|s| s.detected_by).collect();
+    assert_eq!(
+        methods,
+        vec![
+            Some(HeadingMethod::AllCaps),
+            Some(HeadingMethod::Colon),
+            Some(HeadingMethod::Inline),
+        ]
+    );
+    let json = serde_json::to_value(&note.sections[0]).unwrap();
+    assert_eq!(json["detected_by"], "allcaps");
+}
+
+#[test]
+fn custom_sections_from_config_are_kept() {
+    let mut config = Config::default();
+    config
+        .formats
+        .hp
+        .section_order
+        .insert(3, "Social History".to_string());
+    let text = "HPI: cough\nSocial History:\nNonsmoker, lives alone\nPlan: rest";
+    let note = parser::parse_note(text, NoteFormat::Hp, &config, None, 1, HEURISTICS);
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["HPI", "Social History", "Plan"]);
+    assert!(!note.warnings.iter().any(|w| w.code == "unmapped_heading"));
+}
+
+#[test]
+fn fallback_separators_are_configurable() {
+    let mut config = Config::default();
+    let lines = vec![
+        "CC | chest pain".to_string(),
+        "HPI => started today".to_string(),
+    ];
+    let (sections, _) = sectionize::extract_sections(&lines, &[], NoteFormat::Hp, &config, true);
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].name, "Narrative");
+
+    config.parser.fallback_separators = vec!["|".to_string(), "=>".to_string()];
+    let (sections, warnings) =
+        sectionize::extract_sections(&lines, &[], NoteFormat::Hp, &config, true);
+    let names: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Chief Complaint", "HPI"]);
+    assert_eq!(sections[1].content, "started today");
+    assert!(warnings.iter().any(|w| w.code == "fallback_heuristics"));
+}
+
+#[test]
+fn duplicate_policy_controls_repeated_sections() {
+    let text = "Plan:\nRest\nSubjective:\nCough\nPlan:\nFluids";
+    let options = HEURISTICS;
+    let plans = |config: &Config| {
+        let note = parser::parse_note(text, NoteFormat::Soap, config, None, 1, options);
+        note.sections
+            .into_iter()
+            .filter(|s| s.name == "Plan")
+            .map(|s| s.content)
+            .collect::<Vec<_>>()
+    };
+
+    let mut config = Config::default();
+    assert_eq!(plans(&config), vec!["Rest", "Fluids"]);
+
+    config.parser.duplicate_policy = DuplicatePolicy::KeepHighest;
+    assert_eq!(plans(&config), vec!["Rest"]);
+
+    config.parser.duplicate_policy = DuplicatePolicy::Merge;
+    assert_eq!(plans(&config), vec!["Rest\n\nFluids"]);
+}
+
+#[test]
+fn long_narrative_splits_into_numbered_parts() {
+    let mut config = Config::default();
+    config.parser.narrative_chunk_chars = Some(40);
+    let lines: Vec<String> =
+        "first paragraph line one\nline two\n\nsecond paragraph text here\n\nthird"
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+    let (sections, warnings) =
+        sectionize::extract_sections(&lines, &[], NoteFormat::Soap, &config, true);
+    assert!(warnings.iter().any(|w| w.code == "no_headings"));
+    assert_eq!(sections.len(), 2);
+    assert!(sections.iter().all(|s| s.name == "Narrative"));
+    assert_eq!(sections[0].part, Some(1));
+    assert_eq!((sections[0].start_line, sections[0].end_line), (1, 2));
+    assert_eq!(sections[1].part, Some(2));
+    assert_eq!(sections[1].content, "second paragraph text here\n\nthird");
+
+    config.parser.narrative_chunk_chars = None;
+    let (sections, _) = sectionize::extract_sections(&lines, &[], NoteFormat::Soap, &config, true);
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].part, None);
+}
+
+#[test]
+fn explain_traces_each_line() {
+    use clinote::parser::explain::{self, LineKind};
+    let config = Config::default();
+    let text = "SUBJECTIVE\nHeadache\n\nPlan: rest\n";
+    let trace = explain::explain_note(text, NoteFormat::Soap, &config, HEURISTICS);
+    assert_eq!(trace.len(), 4);
+    assert_eq!(trace[0].kind, LineKind::Heading);
+    assert_eq!(trace[0].method, Some(HeadingMethod::AllCaps));
+    assert_eq!(trace[1].kind, LineKind::Body);
+    assert_eq!(trace[1].section.as_deref(), Some("Subjective"));
+    assert_eq!(trace[2].kind, LineKind::Blank);
+    assert_eq!(trace[3].kind, LineKind::Inline);
+    assert_eq!(trace[3].section.as_deref(), Some("Plan"));
+
+    let rendered = explain::format_trace(&trace);
+    assert!(rendered.contains("heading (allcaps)"));
+    assert!(rendered.contains("inline (inline)"));
+}
+
+#[test]
+fn normalization_keeps_indentation_for_nested_content() {
+    let config = Config::default();
+    let text = "Plan:\n  Meds\r\n    - lisinopril 10mg   \n\tFollow up\n";
+    let normalized = parser::normalize::normalize_text(text);
+    let lines: Vec<String> = normalized.lines().map(|l| l.to_string()).collect();
+    assert_eq!(lines[1], "  Meds");
+    assert_eq!(lines[2], "    - lisinopril 10mg");
+    assert_eq!(lines[3], "    Follow up");
+    assert_eq!(parser::normalize::indent_width(&lines[2]), 4);
+    assert_eq!(parser::normalize::indent_width("  \t- tab stop"), 4);
+    assert_eq!(parser::normalize::indent_width("\t\tdeep"), 8);
+
+    let found = headings::scan_headings(&lines, &config);
+    let (sections, _) =
+        sectionize::extract_sections(&lines, &found, NoteFormat::Soap, &config, true);
+    assert_eq!(
+        sections[0].content,
+        "Meds\n    - lisinopril 10mg\n    Follow up"
+    );
+    let depths = sectionize::content_indentation(&lines, &sections[0]);
+    assert_eq!(depths, vec![(2, 0), (3, 2), (4, 2)]);
+}
+
+#[test]
+fn leading_bom_does_not_break_first_heading() {
+    let config = Config::default();
+    let text = "\u{feff}Subjective:\nCough\nPlan:\nRest";
+    assert!(parser::normalize::normalize_text(text).starts_with("Subjective:"));
+    let options = HEURISTICS;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections[0].name, "Subjective");
+    assert_eq!(note.sections[0].content, "Cough");
+}
+
+#[test]
+fn leading_bom_is_dropped_before_bundle_splitting() {
+    let config = Config::default();
+    let dir = std::env::temp_dir().join("clinote_bom_bundle_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("bundle.txt");
+    let text = "\u{feff}2024-01-03 Visit\nPlan: rest\n2024-01-10 Visit\nPlan: recheck";
+    std::fs::write(&path, text).unwrap();
+
+    let read = clinote::util::read_to_string(&path).unwrap();
+    assert!(read.starts_with("2024-01-03"));
+    let (notes, _) = parser::split_bundle(&read, BundleMode::Auto, &config);
+    assert_eq!(notes.len(), 2);
+    assert_eq!(
+        clinote::util::decode_text_with(text.as_bytes(), None).unwrap(),
+        read
+    );
+    assert_eq!(
+        clinote::util::decode_text_with(text.as_bytes(), Some("utf-8")).unwrap(),
+        read
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sections_record_how_their_heading_was_resolved() {
+    use clinote::models::HeadingSource;
+    let mut config = Config::default();
+    config
+        .heading_aliases
+        .insert("Impression".to_string(), "Assessment".to_string());
+    let note = parser::parse_note(
+        "Subjective:\nok\nImpression:\nviral\n",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        HEURISTICS,
+    );
+    assert_eq!(note.sections[0].resolved_by, Some(HeadingSource::Builtin));
+    assert_eq!(
+        note.sections[1].resolved_by,
+        Some(HeadingSource::Alias("Impression".to_string()))
+    );
+    let json = serde_json::to_value(&note.sections[1]).unwrap();
+    assert_eq!(json["resolved_by"]["alias"], "Impression");
+}
+
+#[test]
+fn rewrap_joins_hard_wrapped_prose_only_when_enabled() {
+    let text = "Physical Exam:\nLungs clear to auscultation\nbilaterally without wheeze.\nHeart regular\n\n- no edema\n- pulses\n1. follow up\n";
+    let options = HEURISTICS;
+    let mut config = Config::default();
+    let (plain, _) = parser::extract_candidates(text, NoteFormat::Hp, &config, options);
+    assert!(plain[0].content.contains("auscultation\nbilaterally"));
+
+    config.normalize.rewrap = true;
+    let (wrapped, _) = parser::extract_candidates(text, NoteFormat::Hp, &config, options);
+    assert_eq!(
+        wrapped[0].content,
+        "Lungs clear to auscultation bilaterally without wheeze.\nHeart regular\n\n- no edema\n- pulses\n1. follow up"
+    );
+}
+
+#[test]
+fn notes_summarize_section_confidence() {
+    let config = Config::default();
+    let options = HEURISTICS;
+    let note = parser::parse_note(
+        "Subjective:\nok\nPlan:\nrest",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        options,
+    );
+    assert_eq!(note.confidence.min_confidence, Some(0.85));
+    assert_eq!(note.confidence.max_confidence, Some(0.85));
+    let json = serde_json::to_value(&note).unwrap();
+    assert!(json["mean_confidence"].is_number());
+
+    let empty = parser::build_note(Vec::new(), NoteFormat::Soap, None, 1, Vec::new());
+    let json = serde_json::to_value(&empty).unwrap();
+    assert!(json["min_confidence"].is_null());
+    let back: clinote::models::StructuredNote = serde_json::from_value(json).unwrap();
+    assert_eq!(back.confidence.mean_confidence, None);
+}
+
+#[test]
+fn list_sections_outlines_fixture() {
+    let text = std::fs::read_to_string("tests/fixtures/soap_messy.txt").unwrap();
+    let outline = clinote::list_sections(&text, NoteFormat::Soap, &Config::default(), HEURISTICS);
+    assert_eq!(
+        outline,
+        vec![
+            ("Subjective".to_string(), 1, 2),
+            ("Objective".to_string(), 3, 5),
+            ("Assessment".to_string(), 6, 6),
+            ("Plan".to_string(), 7, 7),
+        ]
+    );
+}
+
+#[test]
+fn accented_headings_match_when_folding_is_enabled() {
+    use clinote::models::HeadingMatching;
+    use clinote::util::normalize_heading_key_with;
+    assert_eq!(
+        clinote::util::normalize_heading_key("Évaluation"),
+        "VALUATION"
+    );
+    assert_eq!(
+        normalize_heading_key_with("évaluation:", HeadingMatching::Unicode),
+        "ÉVALUATION"
+    );
+    assert_eq!(
+        normalize_heading_key_with("Évaluation", HeadingMatching::UnicodeNoAccents),
+        "EVALUATION"
+    );
+
+    let mut config = Config::default();
+    config
+        .heading_aliases
+        .insert("Evaluation".to_string(), "Assessment".to_string());
+    assert!(headings::detect_heading("Évaluation: stable", &config).is_none());
+
+    config.parser.heading_matching = HeadingMatching::UnicodeNoAccents;
+    let heading = headings::detect_heading("Évaluation: stable", &config).unwrap();
+    assert_eq!(heading.0, "Assessment");
+    assert_eq!(heading.1.as_deref(), Some("stable"));
+    let heading = headings::detect_heading("ÉVALUATION", &config).unwrap();
+    assert_eq!(heading.0, "Assessment");
+}
+
+#[test]
+fn preserve_heading_case_keeps_heading_as_written() {
+    use clinote::validate::{self, Template};
+    let text =
+        "PLAN:\nrest\nsubjective: cough for 3 days\nObjective:\nafebrile, clear\nDx: viral URI\n";
+    let options = HEURISTICS;
+    let mut config = Config::default();
+    let canonical = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let names: Vec<&str> = canonical.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Subjective", "Objective", "Assessment", "Plan"]);
+    assert!(canonical
+        .sections
+        .iter()
+        .all(|s| s.canonical_name.is_none()));
+
+    config.parser.preserve_heading_case = true;
+    let preserved = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let names: Vec<&str> = preserved.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["subjective", "Objective", "Dx", "PLAN"]);
+    assert_eq!(preserved.sections[2].canonical(), "Assessment");
+    let issues = validate::validate_note(&preserved, Template::Soap, true);
+    assert!(
+        issues
+            .iter()
+            .all(|i| i.code != "missing_required" && i.code != "unknown_section"),
+        "{:?}",
+        issues
+    );
+}
+
+#[test]
+fn shared_banners_are_stripped_after_splitting() {
+    let text = "MERCY GENERAL HOSPITAL\nDept of Medicine\nSubjective:\ncough\n----- NOTE -----\nMERCY GENERAL HOSPITAL\nDept of Medicine\nSubjective:\nfever\n----- NOTE -----\nMERCY GENERAL HOSPITAL\nSubjective:\nrash\n----- NOTE -----\nSubjective:\nhives";
+    let mut config = Config::default();
+    let (notes, warnings) = parser::split_bundle(text, BundleMode::On, &config);
+    assert!(notes[0].starts_with("MERCY"));
+    assert!(warnings.is_empty());
+
+    config.bundle.strip_banners = true;
+    let (notes, warnings) = parser::split_bundle(text, BundleMode::On, &config);
+    assert_eq!(notes.len(), 4);
+    // "Dept of Medicine" appears in only half the notes, below the 70% default.
+    assert_eq!(notes[0], "Dept of Medicine\nSubjective:\ncough");
+    assert_eq!(notes[2], "Subjective:\nrash");
+    assert_eq!(notes[3], "Subjective:\nhives");
+    // One warning per stripped note, with the banner's lines in the input.
+    let ranges: Vec<(usize, usize)> = warnings
+        .iter()
+        .map(|w| (w.line_start, w.line_end))
+        .collect();
+    assert_eq!(ranges, vec![(1, 1), (6, 6), (11, 11)]);
+    assert!(warnings.iter().all(|w| w.code == "stripped_banner"));
+    assert!(warnings[1]
+        .message
+        .contains("note 2 of 4: MERCY GENERAL HOSPITAL"));
+
+    config.bundle.banner_threshold = 0.5;
+    let (notes, warnings) = parser::split_bundle(text, BundleMode::On, &config);
+    assert_eq!(notes[1], "Subjective:\nfever");
+    assert_eq!((warnings[1].line_start, warnings[1].line_end), (6, 7));
+}
+
+#[test]
+fn keep_raw_content_stores_verbatim_section_lines() {
+    let text = "Subjective:\t cough  \r\n\u{2022} worse at night\r\nPlan:  rest\r\n";
+    let options = HEURISTICS;
+    let mut config = Config::default();
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert!(note.sections.iter().all(|s| s.raw_content.is_none()));
+
+    config.normalize.keep_raw_content = true;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections[0].content, "cough\n- worse at night");
+    assert_eq!(
+        note.sections[0].raw_content.as_deref(),
+        Some("Subjective:\t cough  \r\n\u{2022} worse at night")
+    );
+    assert_eq!(note.sections[1].raw_content.as_deref(), Some("Plan:  rest"));
+
+    assert_eq!(
+        parser::normalize::raw_lines("a\r\nb\rc\n\nd"),
+        ["a\r\n", "b\r", "c\n", "\n", "d"]
+    );
+}
+
+#[test]
+fn bundled_notes_keep_crlf_and_indentation() {
+    let text = "Subjective:\r\n  cough\r\n----- NOTE -----\r\n  Plan:\r\n  rest\r\n";
+    let mut config = Config::default();
+    config.normalize.keep_raw_content = true;
+    let (notes, _) = parser::split_bundle(text, BundleMode::On, &config);
+    assert_eq!(notes, ["Subjective:\r\n  cough", "  Plan:\r\n  rest"]);
+
+    let options = HEURISTICS;
+    let note = parser::parse_note(&notes[1], NoteFormat::Soap, &config, None, 2, options);
+    assert_eq!(note.sections[0].name, "Plan");
+    assert_eq!(
+        note.sections[0].raw_content.as_deref(),
+        Some("  Plan:\r\n  rest")
+    );
+}
+
+#[test]
+fn resolve_heading_reports_canonical_name_and_method() {
+    let mut config = Config::default();
+    config
+        .heading_aliases
+        .insert("Impression".to_string(), "Assessment".to_string());
+
+    let builtin = clinote::resolve_heading("Hx:", &config);
+    assert_eq!(builtin.canonical.as_deref(), Some("PMH"));
+    assert_eq!(builtin.method, "builtin");
+
+    let alias = clinote::resolve_heading("impression", &config);
+    assert_eq!(alias.canonical.as_deref(), Some("Assessment"));
+    assert_eq!(alias.method, "alias");
+    assert_eq!(alias.alias.as_deref(), Some("Impression"));
+
+    let unmapped = clinote::resolve_heading("Social History", &config);
+    assert_eq!(unmapped.canonical, None);
+    assert_eq!(unmapped.method, "none");
+
+    config
+        .formats
+        .discharge
+        .heading_aliases
+        .insert("Impression".to_string(), "Discharge Diagnosis".to_string());
+    let scoped = clinote::resolve_heading_for("Impression:", &config, Some(NoteFormat::Discharge));
+    assert_eq!(scoped.canonical.as_deref(), Some("Discharge Diagnosis"));
+    assert_eq!(scoped.alias.as_deref(), Some("Impression"));
+    let other = clinote::resolve_heading_for("Impression:", &config, Some(NoteFormat::Soap));
+    assert_eq!(other.canonical.as_deref(), Some("Assessment"));
+}
+
+#[test]
+fn dense_demographics_header_is_not_scanned_for_headings() {
+    let text = "Patient: Jane Doe\nDOB: 1990-01-01\nMRN: 448812\nService: Medicine\nSubjective: cough for 3 days\nPlan: fluids\nService: follow up in clinic";
+    let options = HEURISTICS;
+    let mut config = Config::default();
+    config
+        .formats
+        .soap
+        .section_order
+        .push("Service".to_string());
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(headings::demographics_block_len(&lines, &config), 4);
+    let sections = parser::list_sections(text, NoteFormat::Soap, &config, options);
+    assert_eq!(
+        sections,
+        [
+            ("Subjective".to_string(), 5, 5),
+            ("Plan".to_string(), 6, 6),
+            ("Service".to_string(), 7, 7),
+        ]
+    );
+
+    config.parser.demographic_fields.clear();
+    let sections = parser::list_sections(text, NoteFormat::Soap, &config, options);
+    assert_eq!(sections.len(), 4);
+    assert_eq!(sections[2], ("Service".to_string(), 4, 4));
+}
+
+#[test]
+fn id_template_builds_note_ids() {
+    let options = HEURISTICS;
+    let mut config = Config::default();
+    let note = parser::parse_note("Plan:\nrest", NoteFormat::Soap, &config, None, 3, options);
+    assert!(note.id.starts_with("note-3-"));
+
+    config.id_template = "{stem}-{format}-{index}".to_string();
+    let notes = parser::parse_notes(
+        "Plan:\nrest\n----- NOTE -----\nPlan:\nfluids",
+        NoteFormat::Soap,
+        &config,
+        Some("notes/visit_0042.txt".to_string()),
+        0,
+        options,
+    );
+    let ids: Vec<&str> = notes.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(ids, ["visit_0042-soap-1", "visit_0042-soap-2"]);
+}
+
+#[test]
+fn detects_markdown_atx_headings() {
+    let config = Config::default();
+    let heading = headings::detect_heading_with_method("## Assessment", &config).unwrap();
+    assert_eq!(heading.0, "Assessment");
+    assert_eq!(heading.1, None);
+    assert_eq!(heading.2, HeadingMethod::Atx);
+
+    let heading = headings::detect_heading_with_method("### Plan: continue meds", &config).unwrap();
+    assert_eq!(heading.0, "Plan");
+    assert_eq!(heading.1.as_deref(), Some("continue meds"));
+    assert_eq!(heading.2, HeadingMethod::Inline);
+
+    assert_eq!(
+        headings::detect_heading("# Physical Exam #", &config).map(|h| h.0),
+        Some("Physical Exam".to_string())
+    );
+    assert!(headings::detect_heading("###", &config).is_none());
+    assert!(headings::detect_heading("##   ", &config).is_none());
+    assert!(headings::detect_heading("####### Plan", &config).is_none());
+
+    let note = parser::parse_note(
+        "## Subjective\ncough\n## Plan\nrest",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        HEURISTICS,
+    );
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Subjective", "Plan"]);
+}
+
+#[test]
+fn sections_keep_the_heading_as_written() {
+    let config = Config::default();
+    let note = parser::parse_note(
+        "Chief Complaint: cough\nHx: asthma, GERD\nPLAN:\nrest",
+        NoteFormat::Hp,
+        &config,
+        None,
+        1,
+        HEURISTICS,
+    );
+    let pmh = note.sections.iter().find(|s| s.name == "PMH").unwrap();
+    assert_eq!(pmh.raw_heading.as_deref(), Some("Hx"));
+    assert!(note
+        .sections
+        .iter()
+        .filter(|s| s.name != "PMH")
+        .all(|s| s.raw_heading.is_none()));
+
+    let markdown = clinote::render::markdown::render_notes(std::slice::from_ref(&note));
+    assert!(markdown.contains("## PMH (Hx)"));
+    assert!(markdown.contains("## Plan\n"));
+    let json = serde_json::to_value(&note).unwrap();
+    assert!(json["sections"][0].get("raw_heading").is_none());
+}
+
+#[test]
+fn only_a_bare_dash_means_stdio() {
+    use clinote::util::is_stdio;
+    use std::path::Path;
+    assert!(is_stdio(Path::new("-")));
+    assert!(!is_stdio(Path::new("./-")));
+    assert!(!is_stdio(Path::new("-.json")));
+}
+
+#[test]
+fn numbered_headings_drop_their_ordinal() {
+    let config = Config::default();
+    assert_eq!(
+        headings::detect_heading("1. Plan: titrate insulin", &config),
+        Some(("Plan".to_string(), Some("titrate insulin".to_string())))
+    );
+    assert_eq!(
+        headings::detect_heading_with_method("2) ASSESSMENT", &config).map(|h| h.2),
+        Some(HeadingMethod::AllCaps)
+    );
+    assert_eq!(
+        headings::detect_heading_with_method("3. Subjective:", &config).map(|h| h.2),
+        Some(HeadingMethod::Colon)
+    );
+    assert!(headings::detect_heading("1.", &config).is_none());
+    assert!(headings::detect_heading("2)  ", &config).is_none());
+    assert!(headings::detect_heading("1. Continue metformin", &config).is_none());
+
+    let note = parser::parse_note(
+        "1. SUBJECTIVE\ncough for two days\n2) Plan: rest",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        NO_HEURISTICS,
+    );
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Subjective", "Plan"]);
+    assert!(note.sections.iter().all(|s| s.raw_heading.is_none()));
+}
+
+#[test]
+fn format_scoped_aliases_take_precedence_over_global_ones() {
+    let mut config = Config::default();
+    config
+        .heading_aliases
+        .insert("Hx".to_string(), "PMH".to_string());
+    config
+        .formats
+        .discharge
+        .heading_aliases
+        .insert("Dx".to_string(), "Discharge Dx".to_string());
+    let options = NO_HEURISTICS;
+    let first_section = |format| {
+        parser::parse_note("Dx: pneumonia", format, &config, None, 1, options).sections[0]
+            .name
+            .clone()
+    };
+    assert_eq!(first_section(NoteFormat::Discharge), "Discharge Dx");
+    assert_eq!(first_section(NoteFormat::Soap), "Assessment");
+    assert_eq!(
+        headings::canonicalize_heading_for("Hx", &config, Some(NoteFormat::Discharge)).as_deref(),
+        Some("PMH")
+    );
+    assert_eq!(
+        headings::canonicalize_heading("Dx", &config).as_deref(),
+        Some("Assessment")
+    );
+}
+
+#[test]
+fn min_confidence_drops_low_confidence_sections() {
+    let config = Config::default();
+    let text = "CC - chest pain\nPlan - follow up in clinic";
+    let extract = |min_confidence| {
+        parser::extract_candidates(
+            text,
+            NoteFormat::Hp,
+            &config,
+            parser::ParseOptions {
+                apply_heuristics: true,
+                min_confidence,
+            },
+        )
+    };
+    let (kept, warnings) = extract(0.0);
+    assert!(!kept.is_empty());
+    assert!(kept.iter().all(|c| c.confidence < 0.7));
+    assert!(warnings.iter().all(|w| w.code != "low_confidence_dropped"));
+
+    let (dropped, warnings) = extract(0.7);
+    assert!(dropped.is_empty());
+    let notices: Vec<_> = warnings
+        .iter()
+        .filter(|w| w.code == "low_confidence_dropped")
+        .collect();
+    assert_eq!(notices.len(), kept.len());
+    assert!(notices[0].message.contains("0.60"));
+}
+
+#[test]
+fn merge_duplicate_sections_joins_repeated_headings() {
+    let text = "Objective:\nBP 120/80\nAssessment:\nViral URI\nObjective:\nLungs clear";
+    let options = NO_HEURISTICS;
+    let mut config = Config::default();
+    let (separate, _) = parser::extract_candidates(text, NoteFormat::Soap, &config, options);
+    let objectives: Vec<_> = separate.iter().filter(|c| c.name == "Objective").collect();
+    assert_eq!(objectives.len(), 2);
+
+    config.parser.merge_duplicate_sections = true;
+    let (merged, _) = parser::extract_candidates(text, NoteFormat::Soap, &config, options);
+    let objective: Vec<_> = merged.iter().filter(|c| c.name == "Objective").collect();
+    assert_eq!(objective.len(), 1);
+    assert_eq!(objective[0].content, "BP 120/80\n\nLungs clear");
+    assert_eq!((objective[0].start_line, objective[0].end_line), (1, 6));
+    let weakest = objectives
+        .iter()
+        .map(|c| c.confidence)
+        .fold(f32::MAX, f32::min);
+    assert_eq!(objective[0].confidence, weakest);
+
+    let mut by_policy = Config::default();
+    by_policy.parser.duplicate_policy = DuplicatePolicy::Merge;
+    let (via_policy, _) = parser::extract_candidates(text, NoteFormat::Soap, &by_policy, options);
+    assert_eq!(
+        format!("{:?}", via_policy),
+        format!("{:?}", merged),
+        "merge_duplicate_sections is an alias for duplicate_policy = \"merge\""
+    );
+
+    let part = |content: &str, line: usize, confidence: f32| SectionCandidate {
+        name: "Objective".to_string(),
+        raw_heading: "Objective".to_string(),
+        content: content.to_string(),
+        start_line: line,
+        end_line: line,
+        confidence,
+        detected_by: HeadingMethod::Colon,
+        resolved_by: None,
+        part: None,
+        display_name: None,
+        raw_content: None,
+        vitals: None,
+    };
+    let group = vec![part("BP 120/80", 1, 0.85), part("Lungs clear", 5, 0.6)];
+    let merged = sectionize::resolve_duplicates(group.clone(), DuplicatePolicy::Merge);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].confidence, 0.6);
+    assert_eq!((merged[0].start_line, merged[0].end_line), (1, 5));
+    let highest = sectionize::resolve_duplicates(group, DuplicatePolicy::KeepHighest);
+    assert_eq!(highest.len(), 1);
+    assert_eq!(highest[0].content, "BP 120/80");
+    assert_eq!(highest[0].confidence, 0.85);
+}
+
+#[test]
+fn sections_carry_candidate_line_spans() {
+    let config = Config::default();
+    let text = "Patient: Demo\n\nSubjective:\nFeels well\nstill tired\n\nPlan: Rest";
+    let options = HEURISTICS;
+    let (candidates, _) = parser::extract_candidates(text, NoteFormat::Soap, &config, options);
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections.len(), candidates.len());
+    for (section, candidate) in note.sections.iter().zip(&candidates) {
+        let span = section.span.expect("span");
+        assert_eq!(span.line_start, candidate.start_line);
+        assert_eq!(span.line_end, candidate.end_line);
+    }
+    assert_eq!(note.sections[0].span.unwrap().line_start, 3);
+    assert_eq!(note.sections[1].span.unwrap().line_start, 7);
+
+    let json = serde_json::to_value(&note).unwrap();
+    assert_eq!(json["sections"][1]["span"]["line_start"], 7);
+}
+
+#[test]
+fn detects_setext_underlined_headings() {
+    let config = Config::default();
+    let text = "Assessment\n----------\nStable on rounds\n\n1. Plan\n=====\nRest\n\nNot a heading\n-----\nkept";
+    let options = HEURISTICS;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let names: Vec<_> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Assessment", "Plan"]);
+    assert_eq!(note.sections[0].content, "Stable on rounds");
+    assert_eq!(note.sections[0].detected_by, Some(HeadingMethod::Setext));
+    assert!(note.sections[1].content.starts_with("Rest"));
+    assert!(note.sections[1]
+        .content
+        .contains("Not a heading\n-----\nkept"));
+}
+
+#[test]
+fn preserve_bullets_keeps_original_markers() {
+    let mut config = Config::default();
+    let text = "Plan:\n* item one\n\u{2022} item two";
+    let options = HEURISTICS;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections[0].content, "- item one\n- item two");
+
+    config.normalize.preserve_bullets = true;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections[0].content, "* item one\n\u{2022} item two");
+}
+
+#[test]
+fn bundle_splits_on_configured_date_patterns() {
+    let mut config = Config::default();
+    let text = "03.01.2024 Visit\nSubjective: cough\n\n10.01.2024 Visit\nPlan: rest\n\n17.01.2024 Visit\nPlan: recheck";
+    let (notes, _) = parser::split_bundle(text, BundleMode::Auto, &config);
+    assert_eq!(notes.len(), 1);
+
+    config
+        .bundle
+        .date_patterns
+        .push(r"^\s*\d{2}\.\d{2}\.\d{4}".to_string());
+    let (notes, _) = parser::split_bundle(text, BundleMode::Auto, &config);
+    assert_eq!(notes.len(), 3);
+    assert!(notes[1].starts_with("10.01.2024"));
+
+    config.bundle.min_dates_to_split = 4;
+    let (notes, _) = parser::split_bundle(text, BundleMode::Auto, &config);
+    assert_eq!(notes.len(), 1);
+}
+
+#[test]
+fn parse_vitals_tags_objective_readings() {
+    let mut config = Config::default();
+    let text =
+        "Subjective:\nHeadache\nObjective:\nBP 120/80 HR 72 RR 16 T 98.6\nSpO2: 97 %\nPlan:\nRest";
+    let options = HEURISTICS;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert!(note.sections.iter().all(|s| s.vitals.is_none()));
+
+    config.parser.parse_vitals = true;
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let objective = note
+        .sections
+        .iter()
+        .find(|s| s.name == "Objective")
+        .unwrap();
+    assert_eq!(
+        objective.content,
+        "BP 120/80 HR 72 RR 16 T 98.6\nSpO2: 97 %"
+    );
+    let vitals = objective.vitals.as_ref().unwrap();
+    assert_eq!(vitals["bp"], "120/80");
+    assert_eq!(vitals["hr"], "72");
+    assert_eq!(vitals["rr"], "16");
+    assert_eq!(vitals["temp"], "98.6");
+    assert_eq!(vitals["spo2"], "97%");
+    assert!(note
+        .sections
+        .iter()
+        .filter(|s| s.name != "Objective")
+        .all(|s| s.vitals.is_none()));
+
+    let json = serde_json::to_string(objective).unwrap();
+    assert!(json.contains("\"vitals\":{\"bp\":\"120/80\""));
+
+    config.parser.preserve_heading_case = true;
+    config.formats.soap.section_order = ["Subjective", "OBJECTIVE", "Plan"]
+        .map(String::from)
+        .to_vec();
+    let text = text.replace("Objective:", "OBJECTIVE:");
+    let note = parser::parse_note(&text, NoteFormat::Soap, &config, None, 1, options);
+    let objective = note
+        .sections
+        .iter()
+        .find(|s| s.name == "OBJECTIVE")
+        .unwrap();
+    assert_eq!(objective.vitals.as_ref().unwrap()["bp"], "120/80");
+}
+
+#[test]
+fn fallback_section_name_is_configurable() {
+    let config = Config {
+        fallback_section_name: "Unstructured".to_string(),
+        ..Config::default()
+    };
+    let options = NO_HEURISTICS;
+
+    let text = "patient seen today, doing well overall";
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    assert_eq!(note.sections.len(), 1);
+    assert_eq!(note.sections[0].name, "Unstructured");
+    assert!(note.warnings[0].message.contains("grouped as Unstructured"));
+
+    let issues = clinote::validate::validate_parsed_note(
+        &note,
+        text,
+        clinote::validate::Template::Soap,
+        false,
+        &config,
+    );
+    assert!(!issues.iter().any(|i| i.code == "unknown_section"));
+
+    // A discharge heading has no SOAP mapping, so it lands in the fallback too.
+    let text = "Subjective:\nCough\nDisposition:\nHome";
+    let note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Subjective", "Unstructured"]);
+}
+
+#[test]
+fn all_caps_heading_with_inline_text_keeps_the_text() {
+    use clinote::models::HeadingMethod;
+    let config = Config::default();
+    for (line, inline) in [
+        ("PLAN: titrate", "titrate"),
+        ("PLAN: TITRATE LISINOPRIL", "TITRATE LISINOPRIL"),
+        ("1. PLAN: titrate", "titrate"),
+        ("## PLAN: titrate", "titrate"),
+    ] {
+        let (heading, content, method) =
+            headings::detect_heading_with_method(line, &config).unwrap();
+        assert_eq!(heading, "Plan", "{}", line);
+        assert_eq!(content.as_deref(), Some(inline), "{}", line);
+        assert_eq!(method, HeadingMethod::Inline, "{}", line);
+    }
+
+    let options = HEURISTICS;
+    let note = parser::parse_note(
+        "SUBJECTIVE: cough\nPLAN: titrate\n\nrecheck in 2 weeks",
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        options,
+    );
+    let plan = note.sections.iter().find(|s| s.name == "Plan").unwrap();
+    assert_eq!(plan.content, "titrate\n\nrecheck in 2 weeks");
+}
+
+#[test]
+fn sort_sections_uses_canonical_order_with_placeholders() {
+    let config = Config::default();
+    let options = HEURISTICS;
+    let text = "Plan:\nRest\nDisposition:\nHome\nSubjective:\nCough";
+    let mut note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    let detected_hash = note.content_hash.clone();
+    parser::sort_sections(&mut note, &config);
+
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["Subjective", "Objective", "Assessment", "Plan", "Narrative"]
+    );
+    for placeholder in &note.sections[1..3] {
+        assert_eq!(placeholder.content, "");
+        assert_eq!(placeholder.confidence, 0.0);
+        assert!(placeholder.span.is_none());
+    }
+    assert_eq!(note.sections[0].content, "Cough");
+    assert_eq!(note.sections[3].content, "Rest");
+    assert_eq!(note.sections[4].content, "Home");
+    assert_eq!(note.confidence.min_confidence, Some(0.0));
+    assert_ne!(note.content_hash, detected_hash);
+
+    let template = clinote::config::CustomTemplate {
+        name: "Brief".to_string(),
+        format: NoteFormat::Soap,
+        required_sections: vec![
+            vec!["Subjective".to_string()],
+            vec!["Assessment".to_string(), "Plan".to_string()],
+        ],
+        optional_sections: Vec::new(),
+    };
+    let rules = clinote::validate::TemplateRules::custom(&template);
+    let mut note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    parser::sort_sections_with(&mut note, &rules, &config);
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Subjective", "Plan", "Narrative"]);
+
+    let text = "Subjective:\nCough";
+    let mut note = parser::parse_note(text, NoteFormat::Soap, &config, None, 1, options);
+    parser::sort_sections_with(&mut note, &rules, &config);
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Subjective", "Assessment"]);
+}
@@ -1,4 +1,8 @@
-use clinote::models::{CsvLayout, Metadata, NoteFormat, Section, StructuredNote};
+use clinote::config::{Config, CsvConfig};
+use clinote::models::{
+    ConfidenceSummary, CsvLayout, HeadingMethod, Metadata, NoteFormat, Section, Span,
+    StructuredNote,
+};
 use clinote::render::{self, OutputFormat};
 
 fn sample_note() -> StructuredNote {
@@ -11,8 +15,18 @@ fn sample_note() -> StructuredNote {
             name: "Subjective".to_string(),
             content: "Synthetic subjective content".to_string(),
             confidence: 0.9,
+            detected_by: None,
+            resolved_by: None,
+            part: None,
+            canonical_name: None,
+            raw_content: None,
+            raw_heading: None,
+            span: None,
+            vitals: None,
         }],
         warnings: Vec::new(),
+        content_hash: String::new(),
+        confidence: ConfidenceSummary::default(),
         metadata: Metadata {
             generated_at: "2024-01-01T00:00:00Z".to_string(),
             tool_version: "0.1.0".to_string(),
@@ -41,3 +55,253 @@ fn renders_csv_wide() {
     let output = render::render_notes(&[note], OutputFormat::Csv, CsvLayout::Wide).unwrap();
     assert!(output.contains("Subjective"));
 }
+
+#[test]
+fn csv_layout_honors_per_format_override() {
+    let mut csv = CsvConfig::default();
+    csv.by_format.insert(NoteFormat::Discharge, CsvLayout::Long);
+
+    let soap = sample_note();
+    assert_eq!(
+        render::csv::resolve_layout(std::slice::from_ref(&soap), &csv),
+        Some(CsvLayout::Wide)
+    );
+
+    let mut discharge = sample_note();
+    discharge.format = NoteFormat::Discharge;
+    assert_eq!(
+        render::csv::resolve_layout(std::slice::from_ref(&discharge), &csv),
+        Some(CsvLayout::Long)
+    );
+    assert_eq!(render::csv::resolve_layout(&[soap, discharge], &csv), None);
+}
+
+#[test]
+fn renders_tsv_quoting_only_tabs_and_line_breaks() {
+    let mut note = sample_note();
+    note.sections[0].content = "Line one, with comma".to_string();
+    let output = render::render_notes(&[note.clone()], OutputFormat::Tsv, CsvLayout::Long).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("note_id\tformat\t"));
+    assert!(lines[1].ends_with("\tLine one, with comma"));
+    assert!(!output.contains('"'));
+
+    note.sections[0].content = "Line one\n\tLine two".to_string();
+    let output = render::render_notes(&[note], OutputFormat::Tsv, CsvLayout::Long).unwrap();
+    assert!(output.ends_with("\t\"Line one\n\tLine two\"\n"));
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_reader(output.as_bytes());
+    let record = reader.records().next().unwrap().unwrap();
+    assert_eq!(&record[5], "Line one\n\tLine two");
+}
+
+#[test]
+fn renders_wide_tsv_with_tab_separated_header() {
+    let mut note = sample_note();
+    note.sections[0].content = "Pain 3/10, improving\twith rest".to_string();
+    let output = render::render_notes(&[note], OutputFormat::Tsv, CsvLayout::Wide).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "id\tformat\tsource_file\tnote_index\tSubjective");
+    assert!(lines[1].ends_with("\t\"Pain 3/10, improving\twith rest\""));
+}
+
+#[test]
+fn markdown_uses_configured_note_separator() {
+    let mut config = Config::default();
+    config.markdown.note_separator = "<div class=\"pagebreak\"></div>".to_string();
+    let notes = vec![sample_note(), sample_note()];
+    let output =
+        render::render_notes_with(&notes, OutputFormat::Md, CsvLayout::Wide, &config).unwrap();
+    assert!(output.contains("<div class=\"pagebreak\"></div>"));
+    assert!(!output.lines().any(|line| line == "---"));
+
+    let default = render::render_notes(&notes, OutputFormat::Md, CsvLayout::Wide).unwrap();
+    assert!(default.lines().any(|line| line == "---"));
+}
+
+#[test]
+fn csv_wide_marks_missing_sections_apart_from_empty_ones() {
+    let mut with_empty = sample_note();
+    let mut empty_plan = with_empty.sections[0].clone();
+    empty_plan.name = "Plan".to_string();
+    empty_plan.content = String::new();
+    with_empty.sections.push(empty_plan);
+    let mut without_plan = sample_note();
+    without_plan.note_index = 2;
+    let notes = vec![with_empty, without_plan];
+
+    let default = render::render_notes(&notes, OutputFormat::Csv, CsvLayout::Wide).unwrap();
+    let rows: Vec<&str> = default.lines().collect();
+    assert!(rows[1].ends_with(",Synthetic subjective content,"));
+    assert!(rows[2].ends_with(",Synthetic subjective content,"));
+
+    let mut config = Config::default();
+    config.csv.missing_marker = "<missing>".to_string();
+    let marked =
+        render::render_notes_with(&notes, OutputFormat::Csv, CsvLayout::Wide, &config).unwrap();
+    let rows: Vec<&str> = marked.lines().collect();
+    assert!(rows[1].ends_with(",Synthetic subjective content,"));
+    assert!(rows[2].ends_with(",Synthetic subjective content,<missing>"));
+}
+
+#[test]
+fn renders_yaml_single_and_list() {
+    let note = sample_note();
+    let output = render::render_notes(
+        std::slice::from_ref(&note),
+        OutputFormat::Yaml,
+        CsvLayout::Wide,
+    )
+    .unwrap();
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+    assert!(parsed.get("sections").is_some());
+    let back: StructuredNote = serde_yaml::from_str(&output).unwrap();
+    assert_eq!(back.sections[0].name, "Subjective");
+
+    let output =
+        render::render_notes(&[note.clone(), note], OutputFormat::Yaml, CsvLayout::Wide).unwrap();
+    let parsed: Vec<StructuredNote> = serde_yaml::from_str(&output).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(OutputFormat::Yaml.extension(), "yaml");
+}
+
+#[test]
+fn renders_html_with_escaped_content_and_anchors() {
+    let mut note = sample_note();
+    note.sections[0].content = "<script>alert('x')</script> & BP > 140".to_string();
+    let mut follow_up = note.sections[0].clone();
+    follow_up.name = "Follow-up".to_string();
+    follow_up.content = "2 weeks".to_string();
+    note.sections.push(follow_up);
+    let output = render::render_notes(&[note], OutputFormat::Html, CsvLayout::Wide).unwrap();
+    assert!(output.starts_with("<!DOCTYPE html>"));
+    assert!(output.contains("<article id=\"note-1\""));
+    assert!(output.contains("<section id=\"note-1-subjective\" data-confidence=\"0.90\">"));
+    assert!(output.contains("<section id=\"note-1-follow-up\" "));
+    assert!(output.contains("<h2>Subjective</h2>"));
+    assert!(output
+        .contains("<pre>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; BP &gt; 140</pre>"));
+    assert!(!output.contains("<script>"));
+    assert_eq!(OutputFormat::Html.extension(), "html");
+
+    let mut note = sample_note();
+    note.sections[0].detected_by = Some(HeadingMethod::Inline);
+    note.sections[0].span = Some(Span {
+        line_start: 3,
+        line_end: 7,
+    });
+    let output = render::render_notes(&[note], OutputFormat::Html, CsvLayout::Wide).unwrap();
+    assert!(output.contains(
+        "<section id=\"note-1-subjective\" data-confidence=\"0.90\" data-detected-by=\"inline\" data-lines=\"3-7\">"
+    ));
+}
+
+#[test]
+fn renders_ndjson_one_line_per_note() {
+    let single =
+        render::render_notes(&[sample_note()], OutputFormat::Ndjson, CsvLayout::Wide).unwrap();
+    assert_eq!(single.lines().count(), 1);
+    assert!(single.starts_with('{') && single.ends_with("}\n"));
+
+    let mut second = sample_note();
+    second.note_index = 2;
+    second.sections[0].content = "Line one\nLine two".to_string();
+    let output = render::render_notes(
+        &[sample_note(), second, sample_note()],
+        OutputFormat::Ndjson,
+        CsvLayout::Wide,
+    )
+    .unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    for line in &lines {
+        let parsed: StructuredNote = serde_json::from_str(line).unwrap();
+        assert_eq!(parsed.sections.len(), 1);
+    }
+    assert_eq!(OutputFormat::Ndjson.extension(), "ndjson");
+}
+
+#[test]
+fn renders_fhir_composition_with_sections() {
+    let single =
+        render::render_notes(&[sample_note()], OutputFormat::Fhir, CsvLayout::Wide).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&single).unwrap();
+    assert_eq!(value["resourceType"], "Composition");
+    assert_eq!(value["status"], "final");
+    assert_eq!(value["identifier"]["value"], sample_note().id);
+    assert_eq!(value["date"], sample_note().metadata.generated_at);
+    let sections = value["section"].as_array().unwrap();
+    assert_eq!(sections.len(), sample_note().sections.len());
+    assert_eq!(sections[0]["title"], sample_note().sections[0].name);
+
+    let output = render::render_notes(
+        &[sample_note(), sample_note()],
+        OutputFormat::Fhir,
+        CsvLayout::Wide,
+    )
+    .unwrap();
+    let bundle: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(bundle["resourceType"], "Bundle");
+    assert_eq!(bundle["entry"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        bundle["entry"][0]["resource"]["resourceType"],
+        "Composition"
+    );
+    assert_eq!(OutputFormat::Fhir.extension(), "fhir.json");
+}
+
+#[test]
+fn markdown_roundtrips_back_into_notes() {
+    let mut first = sample_note();
+    first.sections.push(Section {
+        name: "Plan".to_string(),
+        content: String::new(),
+        raw_heading: Some("P".to_string()),
+        ..first.sections[0].clone()
+    });
+    let mut second = sample_note();
+    second.format = NoteFormat::Hp;
+    second.source_file = None;
+    second.sections[0].name = "HPI".to_string();
+    second.sections[0].content = "Line one\n\nLine two".to_string();
+
+    let md = render::markdown::render_notes(&[first.clone(), second.clone()]);
+    let parsed = render::markdown::parse_markdown(&md).unwrap();
+    assert_eq!(parsed.len(), 2);
+    for (parsed, original) in parsed.iter().zip([&first, &second]) {
+        assert_eq!(parsed.format, original.format);
+        assert_eq!(parsed.source_file, original.source_file);
+        let names: Vec<_> = parsed.sections.iter().map(|s| &s.name).collect();
+        let expected: Vec<_> = original.sections.iter().map(|s| &s.name).collect();
+        assert_eq!(names, expected);
+        for (a, b) in parsed.sections.iter().zip(&original.sections) {
+            assert_eq!(a.content, b.content);
+            assert_eq!(a.raw_heading, b.raw_heading);
+        }
+    }
+    assert!(render::markdown::parse_markdown("## Plan\nno note heading").is_err());
+}
+
+#[test]
+fn json_envelope_is_opt_in() {
+    let notes = vec![sample_note()];
+    let mut config = Config::default();
+    let bare =
+        render::render_notes_with(&notes, OutputFormat::Json, CsvLayout::Wide, &config).unwrap();
+    assert!(!bare.contains("schema_version"));
+    assert!(bare.trim_start().starts_with("{\n  \"id\""));
+
+    config.json.wrap_envelope = true;
+    let wrapped =
+        render::render_notes_with(&notes, OutputFormat::Json, CsvLayout::Wide, &config).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&wrapped).unwrap();
+    assert_eq!(value["schema_version"], clinote::models::SCHEMA_VERSION);
+    assert_eq!(value["notes"].as_array().unwrap().len(), 1);
+    assert_eq!(value["notes"][0]["id"], "note-1");
+
+    let parsed = render::json::parse_notes(&wrapped).unwrap();
+    assert_eq!(parsed[0].id, "note-1");
+}
@@ -27,3 +27,77 @@ fn selftest_json_output_parses() {
     let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
     assert!(parsed.get("total_files").is_some());
 }
+
+#[test]
+fn selftest_top_failing_breaks_ties_by_file() {
+    let summary = selftest::run_selftest("tests/fixtures", Template::Soap, false, None).unwrap();
+    for pair in summary.top_failing.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let key_a = (
+            std::cmp::Reverse(a.errors),
+            std::cmp::Reverse(a.warnings),
+            &a.file,
+        );
+        let key_b = (
+            std::cmp::Reverse(b.errors),
+            std::cmp::Reverse(b.warnings),
+            &b.file,
+        );
+        assert!(key_a <= key_b);
+    }
+}
+
+#[test]
+fn selftest_extension_allowlist_applies_to_globs() {
+    let temp_dir = std::env::temp_dir().join("clinote_selftest_ext_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+    std::fs::write(temp_dir.join("a.txt"), "Subjective:\nok").unwrap();
+    std::fs::write(temp_dir.join("b.note"), "Subjective:\nok").unwrap();
+    std::fs::write(temp_dir.join("a.gold.json"), "{}").unwrap();
+
+    let pattern = format!("{}/*", temp_dir.display());
+    let summary = selftest::run_selftest(&pattern, Template::Soap, false, None).unwrap();
+    assert_eq!(summary.total_files, 1);
+
+    let options = selftest::SelftestOptions {
+        extensions: vec!["txt".to_string(), "note".to_string()],
+        ..Default::default()
+    };
+    let summary = selftest::run_selftest_with(&pattern, Template::Soap, false, None, &options).unwrap();
+    assert_eq!(summary.total_files, 2);
+    let dir = temp_dir.display().to_string();
+    let summary = selftest::run_selftest_with(&dir, Template::Soap, false, None, &options).unwrap();
+    assert_eq!(summary.total_files, 2);
+
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn selftest_failures_only_writes_failing_files() {
+    let temp_dir = std::env::temp_dir().join("clinote_selftest_failures_only_test");
+    let fixtures = temp_dir.join("fixtures");
+    let out_dir = temp_dir.join("out");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&fixtures).unwrap();
+    std::fs::write(
+        fixtures.join("good.txt"),
+        "Subjective:\nPatient reports a mild headache\nObjective:\nVitals stable and afebrile today\nAssessment:\nTension type headache suspected\nPlan:\nHydration, rest and follow up",
+    )
+    .unwrap();
+    std::fs::write(fixtures.join("bad.txt"), "Plan:\nrest").unwrap();
+
+    let options = selftest::SelftestOptions {
+        failures_only: true,
+        ..Default::default()
+    };
+    let dir = fixtures.display().to_string();
+    let summary =
+        selftest::run_selftest_with(&dir, Template::Soap, true, Some(&out_dir), &options).unwrap();
+    assert_eq!(summary.total_files, 2);
+    assert!(out_dir.join("bad.json").exists());
+    assert!(out_dir.join("bad.issues.json").exists());
+    assert!(!out_dir.join("good.json").exists());
+
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,29 @@
+use clinote::config::Config;
+use clinote::models::NoteFormat;
+use clinote::stats;
+
+#[test]
+fn corpus_stats_count_presence_across_bundled_notes() {
+    let config = Config::default();
+    let files = [
+        "Subjective: cough\nPlan: rest\n----- NOTE -----\nSubjective: fever and chills",
+        "Subjective: sore throat\nObjective: afebrile",
+    ];
+    let stats = stats::corpus_stats(&files, NoteFormat::Soap, &config);
+    assert_eq!(stats.files, 2);
+    assert_eq!(stats.notes, 3);
+
+    let names: Vec<&str> = stats.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Subjective", "Objective", "Plan"]);
+    let subjective = &stats.sections[0];
+    assert_eq!(subjective.notes, 3);
+    assert!((subjective.presence_rate - 1.0).abs() < 1e-9);
+    assert!((subjective.avg_chars - 32.0 / 3.0).abs() < 1e-9);
+    assert!((stats.sections[2].presence_rate - 1.0 / 3.0).abs() < 1e-9);
+
+    let table = stats::format_table(&stats);
+    assert!(table.starts_with("3 notes in 2 files (soap)"));
+    assert!(table.contains("100.0%"));
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["sections"][0]["name"], "Subjective");
+}
@@ -1,5 +1,5 @@
-use clinote::config::Config;
-use clinote::models::{Metadata, NoteFormat, Section, StructuredNote};
+use clinote::config::{Config, ValidationConfig};
+use clinote::models::{ConfidenceSummary, Metadata, NoteFormat, Section, StructuredNote};
 use clinote::parser;
 use clinote::render::{self, OutputFormat};
 use clinote::validate::{self, Severity, Template};
@@ -21,9 +21,19 @@ fn make_note(format: NoteFormat, sections: Vec<(&str, &str)>) -> StructuredNote
                 name: name.to_string(),
                 content: content.to_string(),
                 confidence: 0.9,
+                detected_by: None,
+                resolved_by: None,
+                part: None,
+                canonical_name: None,
+                raw_content: None,
+                raw_heading: None,
+                span: None,
+                vitals: None,
             })
             .collect(),
         warnings: Vec::new(),
+        content_hash: String::new(),
+        confidence: ConfidenceSummary::default(),
         metadata: Metadata {
             generated_at: "2024-01-01T00:00:00Z".to_string(),
             tool_version: "0.1.0".to_string(),
@@ -56,6 +66,7 @@ fn preview_lists_sections() {
         &config,
         parser::ParseOptions {
             apply_heuristics: config.enable_fallback_heuristics,
+            min_confidence: 0.0,
         },
     );
     let note = parser::build_note(candidates, NoteFormat::Soap, None, 1, Vec::new());
@@ -74,6 +85,7 @@ fn convert_output_matches_baseline() {
         &config,
         parser::ParseOptions {
             apply_heuristics: config.enable_fallback_heuristics,
+            min_confidence: 0.0,
         },
     );
     let note = parser::build_note(
@@ -87,3 +99,536 @@ fn convert_output_matches_baseline() {
     let expected = fixture("tests/fixtures/soap_messy.expected.md");
     assert_eq!(output.trim_end(), expected.trim_end());
 }
+
+#[test]
+fn validate_flags_identical_duplicate_sections() {
+    let note = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Plan", "Continue current medications and recheck labs"),
+            ("Plan", "continue current  medications and recheck labs"),
+        ],
+    );
+    let issues = validate::validate_note(&note, Template::Soap, false);
+    assert_eq!(
+        issues
+            .iter()
+            .filter(|i| i.code == "duplicate_section_identical")
+            .count(),
+        1
+    );
+
+    let distinct = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Plan", "Continue current medications and recheck labs"),
+            ("Plan", "Refer to cardiology for stress testing"),
+        ],
+    );
+    let issues = validate::validate_note(&distinct, Template::Soap, false);
+    assert!(issues.iter().any(|i| i.code == "duplicate_section"));
+    assert!(!issues
+        .iter()
+        .any(|i| i.code == "duplicate_section_identical"));
+}
+
+#[test]
+fn template_specs_list_builtin_templates() {
+    let specs = validate::template_specs();
+    let names: Vec<&str> = specs.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["soap", "hp", "discharge", "progress", "operative"]
+    );
+    let soap = &specs[0];
+    assert_eq!(soap.required.len(), 4);
+    assert!(soap.optional.contains(&"Narrative".to_string()));
+}
+
+#[test]
+fn validate_document_reports_per_note_issues() {
+    let config = Config::default();
+    let text = "Subjective:\nPatient reports mild headache since morning\n----- NOTE -----\nPlan:\nReturn if symptoms worsen over the weekend";
+    let results = clinote::validate_document(text, Template::Soap, true, &config);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, 1);
+    assert_eq!(results[1].0, 2);
+    assert!(results[0]
+        .1
+        .iter()
+        .any(|i| i.code == "missing_required" && i.section.as_deref() == Some("Plan")));
+    assert!(results[1]
+        .1
+        .iter()
+        .any(|i| i.code == "missing_required" && i.section.as_deref() == Some("Subjective")));
+}
+
+#[test]
+fn forbid_unknown_sections_escalates_severity() {
+    let note = make_note(
+        NoteFormat::Soap,
+        vec![("Social History", "Nonsmoker, lives alone with spouse")],
+    );
+    let unknown_severity = |issues: Vec<validate::ValidationIssue>| {
+        issues
+            .into_iter()
+            .find(|i| i.code == "unknown_section")
+            .map(|i| i.severity)
+    };
+
+    let default = validate::validate_note(&note, Template::Soap, true);
+    assert_eq!(unknown_severity(default), Some(Severity::Info));
+
+    let options = ValidationConfig {
+        forbid_unknown_sections: true,
+        ..ValidationConfig::default()
+    };
+    let lenient = validate::validate_note_with(&note, Template::Soap, false, &options);
+    assert_eq!(unknown_severity(lenient), Some(Severity::Warn));
+    let strict = validate::validate_note_with(&note, Template::Soap, true, &options);
+    assert_eq!(unknown_severity(strict), Some(Severity::Error));
+}
+
+#[test]
+fn header_dates_must_be_plausible() {
+    let config = Config::default();
+    let today = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    let text = "Patient: Demo\nDOB: 1090-01-01\nVisit Date: 2030-01-01\nAdmit date: 13/45/2020\n\nSubjective:\nDOB: not-a-date";
+    let issues = validate::validate_dates_on(text, &config, today);
+    let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(issues.len(), 3, "{:?}", messages);
+    assert!(issues.iter().all(|i| i.code == "invalid_date"));
+    assert!(messages[0].contains("1090-01-01") && messages[0].contains("implausibly old"));
+    assert!(messages[1].contains("in the future"));
+    assert!(messages[2].contains("not a recognizable date"));
+    assert_eq!(issues[0].span.as_ref().map(|s| s.line_start), Some(2));
+
+    let clean = validate::validate_dates_on("DOB: 1990-01-01\nSubjective:\nok", &config, today);
+    assert!(clean.is_empty());
+}
+
+#[test]
+fn document_report_pairs_sections_with_issues() {
+    let config = Config::default();
+    let text = "Subjective:\nPatient reports mild headache since morning\nHPI: cough\n----- NOTE -----\nPlan:\nReturn if symptoms worsen over the weekend";
+    let report =
+        clinote::reports::document_report(text, Template::Soap, false, &config, "note.txt");
+    assert_eq!(report.input, "note.txt");
+    assert_eq!(report.notes.len(), 2);
+    let first = &report.notes[0];
+    assert_eq!(first.note_index, 1);
+    assert_eq!(first.sections[0].name, "Subjective");
+    assert!(first.warnings.iter().any(|w| w.code == "unmapped_heading"));
+    assert!(first.issues.iter().any(|i| i.code == "missing_required"));
+    assert_eq!(report.notes[1].sections[0].name, "Plan");
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["notes"][0]["issues"].is_array());
+}
+
+#[test]
+fn notes_without_a_date_or_encounter_marker_are_flagged() {
+    let mut config = Config::default();
+    let issues = validate::validate_encounter_date("Subjective:\ncough\nPlan:\nrest", &config);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].code, "no_encounter_date");
+    assert_eq!(issues[0].severity, validate::Severity::Info);
+
+    for dated in [
+        "Visit 2024-03-01\nSubjective:\ncough",
+        "Seen 3/1/24\nPlan:\nrest",
+        "Date: March 1, 2024\nPlan: rest",
+        "Encounter #48213\nPlan: rest",
+    ] {
+        assert!(
+            validate::validate_encounter_date(dated, &config).is_empty(),
+            "{}",
+            dated
+        );
+    }
+
+    config.validation.date_patterns = vec!["MRN \\d+".to_string(), "(".to_string()];
+    assert!(validate::validate_encounter_date("MRN 1234\nPlan: rest", &config).is_empty());
+    assert!(validate::validate_config(&config)
+        .iter()
+        .any(|i| i.code == "invalid_date_pattern"));
+    config.validation.date_patterns.clear();
+    assert!(validate::validate_encounter_date("Plan: rest", &config).is_empty());
+}
+
+#[test]
+fn min_words_by_section_flags_sparse_sections() {
+    let note = make_note(
+        NoteFormat::Hp,
+        vec![("HPI", "Cough for three days"), ("Allergies", "NKDA")],
+    );
+    let mut options = ValidationConfig::default();
+    options.min_words_by_section.insert("hpi".to_string(), 10);
+    let issues = validate::validate_note_with(&note, Template::Hp, false, &options);
+    let sparse: Vec<_> = issues
+        .iter()
+        .filter(|i| i.code == "section_too_few_words")
+        .collect();
+    assert_eq!(sparse.len(), 1);
+    assert_eq!(sparse[0].section.as_deref(), Some("HPI"));
+    assert!(sparse[0].message.contains("4 words"));
+
+    options.min_words_by_section.insert("hpi".to_string(), 4);
+    let issues = validate::validate_note_with(&note, Template::Hp, false, &options);
+    assert!(issues.iter().all(|i| i.code != "section_too_few_words"));
+}
+
+#[test]
+fn placeholder_content_in_required_sections_is_flagged() {
+    let note = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Subjective", "n/a"),
+            ("Objective", "Vitals stable, lungs clear bilaterally"),
+            ("Assessment", "[See note]"),
+            ("Plan", "Deferred"),
+            ("Medications", "N/A"),
+        ],
+    );
+    let issues = validate::validate_note(&note, Template::Soap, false);
+    let flagged: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.code == "placeholder_content")
+        .filter_map(|i| i.section.as_deref())
+        .collect();
+    assert_eq!(flagged, ["Subjective", "Assessment", "Plan"]);
+
+    let options = ValidationConfig {
+        placeholder_phrases: Vec::new(),
+        ..ValidationConfig::default()
+    };
+    let issues = validate::validate_note_with(&note, Template::Soap, false, &options);
+    assert!(issues.iter().all(|i| i.code != "placeholder_content"));
+}
+
+#[test]
+fn progress_template_mirrors_soap_requirements() {
+    let note = make_note(
+        NoteFormat::Progress,
+        vec![
+            ("Interval History", "No acute events since last visit"),
+            ("Events", "Afebrile overnight, tolerating diet"),
+            ("Subjective", "Feels better, pain improved"),
+            ("Objective", "Vitals stable, lungs clear bilaterally"),
+        ],
+    );
+    let issues = validate::validate_note(&note, Template::Progress, true);
+    let missing: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.code == "missing_required")
+        .filter_map(|i| i.section.as_deref())
+        .collect();
+    assert_eq!(missing, ["Assessment", "Plan"]);
+    assert!(issues.iter().all(|i| i.code != "unknown_section"));
+    assert_eq!(Template::Progress.note_format(), NoteFormat::Progress);
+    assert_eq!(
+        Config::default().section_order(NoteFormat::Progress)[0],
+        "Interval History"
+    );
+}
+
+#[test]
+fn min_section_len_is_configurable() {
+    let note = make_note(NoteFormat::Hp, vec![("Allergies", "NKDA"), ("Plan", "")]);
+    let too_short = |options: &ValidationConfig| {
+        validate::validate_note_with(&note, Template::Hp, false, options)
+            .into_iter()
+            .filter(|i| i.code == "section_too_short")
+            .count()
+    };
+    assert_eq!(too_short(&ValidationConfig::default()), 2);
+    let terse = ValidationConfig {
+        min_section_len: 4,
+        ..ValidationConfig::default()
+    };
+    assert_eq!(too_short(&terse), 1);
+    let off = ValidationConfig {
+        min_section_len: 0,
+        ..ValidationConfig::default()
+    };
+    assert_eq!(too_short(&off), 0);
+}
+
+#[test]
+fn sections_out_of_template_order_are_reported() {
+    let note = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Subjective", "Patient reports mild headache since morning"),
+            ("Narrative", "Seen with family present in clinic today"),
+            ("Plan", "Return if symptoms worsen over the weekend"),
+            ("Social History", "Nonsmoker, lives alone with spouse"),
+            ("Objective", "Vitals stable, lungs clear bilaterally"),
+            ("Assessment", "Tension headache, likely benign course"),
+        ],
+    );
+    let out_of_order = |strict| {
+        validate::validate_note(&note, Template::Soap, strict)
+            .into_iter()
+            .filter(|i| i.code == "out_of_order")
+            .collect::<Vec<_>>()
+    };
+    let issues = out_of_order(false);
+    let sections: Vec<&str> = issues.iter().filter_map(|i| i.section.as_deref()).collect();
+    assert_eq!(sections, ["Objective", "Assessment"]);
+    assert!(issues[0].message.contains("'Plan'"));
+    assert!(issues.iter().all(|i| i.severity == Severity::Info));
+    assert!(out_of_order(true)
+        .iter()
+        .all(|i| i.severity == Severity::Warn));
+
+    let ordered = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Subjective", "Patient reports mild headache since morning"),
+            ("Plan", "Return if symptoms worsen over the weekend"),
+            ("Plan", "Follow up with primary care in two weeks"),
+        ],
+    );
+    assert!(validate::validate_note(&ordered, Template::Soap, true)
+        .iter()
+        .all(|i| i.code != "out_of_order"));
+}
+
+#[test]
+fn out_of_order_follows_the_configured_section_order() {
+    let note = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Plan", "Return if symptoms worsen over the weekend"),
+            ("Assessment", "Tension headache, likely benign course"),
+            ("Subjective", "Patient reports mild headache since morning"),
+            ("Objective", "Vitals stable, lungs clear bilaterally"),
+        ],
+    );
+    let out_of_order = |config: &Config| {
+        validate::validate_parsed_note(&note, "", Template::Soap, true, config)
+            .into_iter()
+            .filter(|i| i.code == "out_of_order")
+            .count()
+    };
+    let mut config = Config::default();
+    assert_eq!(out_of_order(&config), 3);
+
+    config.formats.soap.section_order = ["Plan", "Assessment", "Subjective", "Objective"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(out_of_order(&config), 0);
+    let rules = validate::TemplateRules::builtin_with(Template::Soap, &config);
+    assert_eq!(rules.order[0], ["Plan"]);
+}
+
+#[test]
+fn custom_templates_validate_like_builtin_ones() {
+    let template = clinote::config::CustomTemplate {
+        name: "Nursing Assessment".to_string(),
+        format: NoteFormat::Hp,
+        required_sections: vec![
+            vec!["Assessment".to_string()],
+            vec!["Plan".to_string(), "Interventions".to_string()],
+        ],
+        optional_sections: vec!["Allergies".to_string()],
+    };
+    let rules = validate::TemplateRules::custom(&template);
+    let note = make_note(
+        NoteFormat::Hp,
+        vec![
+            ("Interventions", "Repositioned every two hours overnight"),
+            ("Allergies", "No known drug allergies reported"),
+            ("HPI", "Admitted for cellulitis of the left leg"),
+        ],
+    );
+    let issues = validate::validate_note_rules(&note, &rules, true, &ValidationConfig::default());
+    let missing: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.code == "missing_required")
+        .filter_map(|i| i.section.as_deref())
+        .collect();
+    assert_eq!(missing, ["Assessment"]);
+    assert!(issues.iter().any(|i| i.severity == Severity::Error));
+    let unknown: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.code == "unknown_section")
+        .filter_map(|i| i.section.as_deref())
+        .collect();
+    assert_eq!(unknown, ["HPI"]);
+
+    let mut config = Config::default();
+    config.templates.push(template);
+    let rules =
+        validate::TemplateRules::custom(config.custom_template("NURSING ASSESSMENT").unwrap());
+    let results = validate::validate_notes_rules(
+        "Assessment:\nStable overnight without acute complaints\nPlan:\nContinue wound care twice daily",
+        &rules,
+        true,
+        &config,
+        None,
+    );
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.iter().all(|i| i.code != "missing_required"));
+}
+
+#[test]
+fn section_summary_counts_words_and_grade() {
+    let text = "Plan:\nThe cat sat. The dog ran home!\n\nRecheck labs in two weeks?";
+    let config = Config::default();
+    let note = parser::parse_note(
+        text,
+        NoteFormat::Soap,
+        &config,
+        None,
+        1,
+        parser::ParseOptions {
+            apply_heuristics: false,
+            min_confidence: 0.0,
+        },
+    );
+    let summary = validate::summarize_sections(&note);
+    assert_eq!(summary[0].word_count, 12);
+    // 12 words, 3 sentences, 13 syllables.
+    let expected = 0.39 * 4.0 + 11.8 * (13.0 / 12.0) - 15.59;
+    assert!((summary[0].flesch_kincaid_grade - expected).abs() < 1e-4);
+    assert_eq!(validate::flesch_kincaid_grade(""), 0.0);
+}
+
+#[test]
+fn content_rules_flag_plan_without_follow_up_interval() {
+    let options: ValidationConfig = toml::from_str(
+        r#"
+[[rules]]
+section = "plan"
+pattern = '\d+ (days|weeks)'
+severity = "error"
+code = "plan_missing_follow_up"
+message = "Plan has no follow-up interval"
+
+[[rules]]
+section = "Plan"
+pattern = "(?i)tbd"
+must_match = false
+code = "plan_tbd"
+message = "Plan is still TBD"
+"#,
+    )
+    .unwrap();
+    assert_eq!(options.rules.len(), 2);
+    assert!(options.rules[0].must_match);
+
+    let vague = make_note(
+        NoteFormat::Soap,
+        vec![("Plan", "Continue meds, follow up TBD with primary care")],
+    );
+    let issues = validate::validate_note_with(&vague, Template::Soap, false, &options);
+    let rule_issues: Vec<_> = issues
+        .iter()
+        .filter(|i| i.code.starts_with("plan_"))
+        .collect();
+    assert_eq!(rule_issues.len(), 2);
+    assert_eq!(rule_issues[0].code, "plan_missing_follow_up");
+    assert_eq!(rule_issues[0].severity, Severity::Error);
+    assert_eq!(rule_issues[0].message, "Plan has no follow-up interval");
+    assert_eq!(rule_issues[1].severity, Severity::Warn);
+
+    let clear = make_note(
+        NoteFormat::Soap,
+        vec![("Plan", "Continue meds, return in 2 weeks")],
+    );
+    let issues = validate::validate_note_with(&clear, Template::Soap, false, &options);
+    assert!(!issues.iter().any(|i| i.code.starts_with("plan_")));
+}
+
+#[test]
+fn low_confidence_required_sections_are_reported() {
+    let mut note = make_note(
+        NoteFormat::Soap,
+        vec![
+            ("Subjective", "Cough for three days, no fever"),
+            ("Objective", "Lungs clear, afebrile on exam"),
+            ("Assessment", "Viral upper respiratory infection"),
+            ("Plan", "Fluids, rest, return if worse"),
+        ],
+    );
+    // Fallback heading detection scores sections 0.6.
+    note.sections[3].confidence = 0.6;
+
+    let options = ValidationConfig::default();
+    let issues = validate::validate_note_with(&note, Template::Soap, false, &options);
+    let low: Vec<_> = issues
+        .iter()
+        .filter(|i| i.code == "low_confidence_section")
+        .collect();
+    assert_eq!(low.len(), 1);
+    assert_eq!(low[0].severity, Severity::Info);
+    assert_eq!(low[0].section.as_deref(), Some("Plan"));
+    assert!(low[0].message.contains("0.60"));
+
+    let strict = validate::validate_note_with(&note, Template::Soap, true, &options);
+    assert!(strict
+        .iter()
+        .any(|i| i.code == "low_confidence_section" && i.severity == Severity::Warn));
+
+    let off = ValidationConfig {
+        confidence_warn: 0.0,
+        ..ValidationConfig::default()
+    };
+    let issues = validate::validate_note_with(&note, Template::Soap, false, &off);
+    assert!(!issues.iter().any(|i| i.code == "low_confidence_section"));
+}
+
+#[test]
+fn operative_template_requires_surgical_sections() {
+    let config = Config::default();
+    let text = "PREOP DX: Acute appendicitis\nPost-op Diagnosis: Perforated appendicitis\nProcedure Performed: Laparoscopic appendectomy\nFindings: Perforated appendix with localized purulence\nEBL: 25 mL\n";
+    let note = parser::parse_note(
+        text,
+        NoteFormat::Operative,
+        &config,
+        None,
+        1,
+        parser::ParseOptions {
+            apply_heuristics: true,
+            min_confidence: 0.0,
+        },
+    );
+    let names: Vec<&str> = note.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Preoperative Diagnosis",
+            "Postoperative Diagnosis",
+            "Procedure",
+            "Findings",
+            "Estimated Blood Loss"
+        ]
+    );
+
+    let issues = validate::validate_note(&note, Template::Operative, true);
+    let missing: Vec<_> = issues
+        .iter()
+        .filter(|i| i.code == "missing_required")
+        .collect();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].section.as_deref(), Some("Specimens"));
+    assert_eq!(missing[0].severity, Severity::Error);
+    assert!(!issues.iter().any(|i| i.code == "unknown_section"));
+}
+
+#[test]
+fn spans_in_a_bundle_count_from_each_note() {
+    let text = "Subjective:\ncough\n----- NOTE -----\nPlan:\nrest";
+    let notes = validate::validate_notes(text, Template::Soap, false, &Config::default(), None);
+    assert_eq!(notes.len(), 2);
+    let span: Option<validate::Span> = notes[1].0.sections[0].span;
+    assert_eq!(
+        span,
+        Some(validate::Span {
+            line_start: 1,
+            line_end: 2
+        })
+    );
+}
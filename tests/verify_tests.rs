@@ -0,0 +1,24 @@
+use clinote::config::Config;
+use clinote::validate::Template;
+use clinote::verify;
+
+#[test]
+fn verify_reports_lines_outside_sections() {
+    let config = Config::default();
+    let text = "Patient: Synthetic Demo\n\nSubjective:\nFeels well\nPlan:\nRest";
+    let report = verify::verify_document(text, Template::Soap, &config, "note.txt");
+    assert_eq!(report.notes.len(), 1);
+    let note = &report.notes[0];
+    assert_eq!(note.total_lines, 5);
+    assert_eq!(note.dropped_content.len(), 1);
+    assert_eq!(note.dropped_content[0].line, 1);
+    assert_eq!(note.dropped_content[0].text, "Patient: Synthetic Demo");
+}
+
+#[test]
+fn verify_passes_fully_covered_note() {
+    let config = Config::default();
+    let text = "Subjective:\nFeels well\nPlan:\nRest";
+    let report = verify::verify_document(text, Template::Soap, &config, "note.txt");
+    assert_eq!(report.dropped_count(), 0);
+}
@@ -0,0 +1,71 @@
+use clinote::models::{ConfidenceSummary, Metadata, NoteFormat, Section, StructuredNote};
+use clinote::wordfreq;
+use std::fs;
+
+fn note_with(section: &str, content: &str) -> StructuredNote {
+    StructuredNote {
+        id: "note-1".to_string(),
+        format: NoteFormat::Soap,
+        source_file: None,
+        note_index: 1,
+        sections: vec![Section {
+            name: section.to_string(),
+            content: content.to_string(),
+            confidence: 0.9,
+            detected_by: None,
+            resolved_by: None,
+            part: None,
+            canonical_name: None,
+            raw_content: None,
+            raw_heading: None,
+            span: None,
+            vitals: None,
+        }],
+        warnings: Vec::new(),
+        content_hash: String::new(),
+        confidence: ConfidenceSummary::default(),
+        metadata: Metadata {
+            generated_at: "2024-01-01T00:00:00Z".to_string(),
+            tool_version: "0.1.0".to_string(),
+        },
+    }
+}
+
+#[test]
+fn counts_tokens_in_named_section_only() {
+    let notes = vec![
+        note_with("Assessment", "Viral URI, likely viral."),
+        note_with("Plan", "Viral panel"),
+    ];
+    let words = wordfreq::word_frequencies(&notes, "assessment", 1);
+    assert_eq!(words[0].token, "viral");
+    assert_eq!(words[0].count, 2);
+    assert_eq!(words.len(), 3);
+
+    let frequent = wordfreq::word_frequencies(&notes, "Assessment", 2);
+    assert_eq!(frequent.len(), 1);
+}
+
+#[test]
+fn load_notes_skips_non_note_json() {
+    let dir = std::env::temp_dir().join("clinote_wordfreq_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let note = note_with("Assessment", "Stable");
+    fs::write(dir.join("a.json"), serde_json::to_string(&note).unwrap()).unwrap();
+    fs::write(
+        dir.join("b.json"),
+        serde_json::to_string(&vec![note.clone(), note]).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("batch_report.json"),
+        "{\"tool_name\": \"clinote\"}",
+    )
+    .unwrap();
+
+    let notes = wordfreq::load_notes(&dir).unwrap();
+    assert_eq!(notes.len(), 3);
+
+    let _ = fs::remove_dir_all(&dir);
+}